@@ -1,69 +1,487 @@
-use crate::llms::LLMProvider;
-use crate::llms::{self, backend::LLMBackend};
-use once_cell::sync::Lazy;
+use crate::chunking::{chunk_diff, estimate_tokens, truncate_to_budget};
+use crate::commit_message::{
+    clean_plain_message, header_limit_instruction, CommitMessage, MAX_HEADER_LEN,
+    STRUCTURED_OUTPUT_INSTRUCTIONS,
+};
+use crate::commit_style::CommitStyle;
+use crate::config::GIT_AI_CONFIG;
+use crate::diff_analysis;
+use crate::llm_cache;
+use crate::llm_usage;
+use crate::llms::error::LLMError;
+use crate::llms::stream::TokenSink;
+use crate::llms::{
+    backend::{build_fallback_chain, LLMBackend},
+    nollm::NoLLMProvider,
+    CompletionKind, LLMProvider,
+};
+use crate::prompt_templates;
+use colored::*;
+use once_cell::sync::OnceCell;
 use rand::prelude::*;
-use std::error::Error;
-use std::sync::Mutex;
+use std::future::Future;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
 
-pub static BACKEND: Lazy<Mutex<Option<LLMBackend>>> = Lazy::new(|| Mutex::new(None));
+pub static BACKEND: OnceCell<Box<dyn LLMProvider>> = OnceCell::new();
 
-pub fn init_llm_backend() {
-    let backend = LLMBackend::detect_backend();
-    let mut global_backend = BACKEND.lock().unwrap();
-    *global_backend = Some(backend);
+// Whether the last answer came from llm_cache rather than a provider
+static FROM_CACHE: AtomicBool = AtomicBool::new(false);
+
+// Backend calls currently waiting in `cancellable`
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+const PROMPT_OVERHEAD_TOKENS: usize = 512;
+const MIN_CHUNK_TOKENS: usize = 1024;
+const SUMMARY_CONCURRENCY: usize = 4;
+const MAX_REPAIRS: u32 = 2;
+
+// Set up on first use, so commands that never call a model (and the git
+// hooks, where a failure would block `git commit`) don't depend on the
+// backend configuration being valid
+fn backend() -> &'static dyn LLMProvider {
+    BACKEND.get_or_init(build_backend).as_ref()
 }
 
-pub async fn generate_commit_message(
-    diff: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let backend = BACKEND.lock().unwrap();
-    let backend = backend.clone().expect("Backend not initialized!");
-
-    match backend {
-        LLMBackend::OpenAI => llms::openai::OpenAIProvider::generate_commit_message(diff).await,
-        LLMBackend::Azure => llms::azure::AzureOpenAIProvider::generate_commit_message(diff).await,
-        LLMBackend::Ollama => llms::ollama::OllamaProvider::generate_commit_message(diff).await,
-        LLMBackend::Claude => llms::claude::ClaudeProvider::generate_commit_message(diff).await,
-        LLMBackend::Gemini => llms::gemini::GeminiProvider::generate_commit_message(diff).await,
-        LLMBackend::NoLLM => Err(Box::<dyn Error + Send + Sync>::from("no_llm is set")),
+// A backend that can't be set up is reported once and treated as no
+// backend, so callers fall back to their offline paths
+fn build_backend() -> Box<dyn LLMProvider> {
+    let provider = match GIT_AI_CONFIG.fallback.as_deref() {
+        Some(fallback) if !fallback.is_empty() => build_fallback_chain(fallback)
+            .map_err(|e| format!("Failed to set up the fallback chain: {}", e)),
+        _ => match LLMBackend::detect_backend() {
+            Ok(backend) => backend
+                .build_provider()
+                .map_err(|e| format!("Failed to set up '{}' backend: {}", backend.name(), e)),
+            Err(e) => Err(format!("Failed to set up the AI backend: {}", e)),
+        },
+    };
+    provider.unwrap_or_else(|message| {
+        println!(
+            "{}",
+            format!("❌ {}. Continuing without AI.", message).red()
+        );
+        Box::new(NoLLMProvider::default())
+    })
+}
+
+// e.g. "openai (gpt-4)", shown while waiting on the backend
+pub fn backend_label() -> String {
+    backend().label()
+}
+
+// Provider behind the last answer, e.g. the local model when the primary failed
+pub fn answered_by() -> String {
+    if FROM_CACHE.load(Ordering::Relaxed) {
+        return "the response cache".to_string();
     }
+    let (name, model) = backend().answered_by();
+    format!("{} ({})", name, model)
 }
-pub async fn generate_project_scaffolding(
-    prompt: &str,
-    input: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let backend = BACKEND.lock().unwrap();
-    let backend = backend.clone().expect("Backend not initialized!");
 
-    match backend {
-        LLMBackend::OpenAI => {
-            llms::openai::OpenAIProvider::generate_project_scaffolding(prompt, input).await
+// Streaming variants print as the model writes; `stream = false` in .git-ai
+// turns them back into a single blocking call
+// `style` is an extra instruction such as "Keep it terse", see CANDIDATE_STYLES
+pub async fn stream_commit_message(
+    diff: &str,
+    style: Option<&str>,
+    on_token: TokenSink<'_>,
+) -> Result<String, LLMError> {
+    let raw = diff_completion(diff, style, on_token, false).await?;
+    Ok(clean_plain_message(&raw))
+}
+
+// Ask for the JSON shape, validate it, and let the model repair bad output
+pub async fn generate_structured_commit_message(
+    diff: &str,
+    style: Option<&str>,
+    on_token: TokenSink<'_>,
+) -> Result<CommitMessage, LLMError> {
+    let mut raw = diff_completion(diff, style, on_token, true).await?;
+
+    let mut repairs = 0;
+    loop {
+        match CommitMessage::parse(&raw, header_limit()) {
+            Ok(message) => return Ok(message),
+            Err(reason) if repairs < MAX_REPAIRS => {
+                repairs += 1;
+                println!(
+                    "{}",
+                    format!(
+                        "\n🔧 Model output was not valid ({}). Asking for a fix ({}/{})...",
+                        reason, repairs, MAX_REPAIRS
+                    )
+                    .yellow()
+                );
+                let (system_prompt, input) = prompt_templates::load("repair")
+                    .render(&[("error", reason.as_str()), ("output", raw.as_str())]);
+                raw = request(
+                    CompletionKind::structured("repair"),
+                    &system_prompt,
+                    &input,
+                    &mut |_| {},
+                    false,
+                )
+                .await?;
+            }
+            Err(reason) => return Err(LLMError::MalformedResponse(reason)),
         }
-        LLMBackend::Azure => {
-            llms::azure::AzureOpenAIProvider::generate_project_scaffolding(prompt, input).await
+    }
+}
+
+async fn diff_completion(
+    diff: &str,
+    style: Option<&str>,
+    on_token: TokenSink<'_>,
+    structured: bool,
+) -> Result<String, LLMError> {
+    // The repository's own style, the JSON contract and the candidate style
+    // go after the user's template so an override can't drop them
+    let finish_prompt = |system_prompt: String| {
+        let mut system_prompt = system_prompt;
+        if let Some(repo_style) = learned_style() {
+            system_prompt = format!("{}\n\n{}", system_prompt, repo_style.prompt());
         }
-        LLMBackend::Ollama => {
-            llms::ollama::OllamaProvider::generate_project_scaffolding(prompt, input).await
+        if structured {
+            system_prompt = format!(
+                "{}\n\n{}\n{}",
+                system_prompt,
+                STRUCTURED_OUTPUT_INSTRUCTIONS,
+                header_limit_instruction(header_limit())
+            );
         }
-        LLMBackend::Claude => {
-            llms::claude::ClaudeProvider::generate_project_scaffolding(prompt, input).await
+        if let Some(style) = style {
+            system_prompt = format!("{}\n{}", system_prompt, style);
         }
-        LLMBackend::Gemini => {
-            llms::gemini::GeminiProvider::generate_project_scaffolding(prompt, input).await
+        system_prompt
+    };
+
+    let budget = diff_budget();
+    if estimate_tokens(diff) <= budget {
+        let (system_prompt, input) = prompt_templates::load("commit").render(&[("diff", diff)]);
+        return request(
+            CompletionKind {
+                template: "commit",
+                structured,
+            },
+            &finish_prompt(system_prompt),
+            &input,
+            on_token,
+            streaming_enabled(),
+        )
+        .await;
+    }
+
+    // Too big for one request: summarize the pieces, then write the message
+    // from the summaries
+    cancellable(async {
+        let chunks = chunk_diff(diff, budget);
+        println!(
+            "{}",
+            format!(
+                "📚 Staged diff is ~{} tokens, over the {} token budget. Summarizing {} chunks first...",
+                estimate_tokens(diff),
+                budget,
+                chunks.len()
+            )
+            .cyan()
+        );
+
+        let summaries = summarize_chunks(chunks).await?;
+        let summaries = truncate_to_budget(&summaries.join("\n\n"), budget);
+        let (system_prompt, input) = prompt_templates::load("commit-from-summaries")
+            .render(&[("summaries", summaries.as_str())]);
+        with_retries(
+            CompletionKind {
+                template: "commit-from-summaries",
+                structured,
+            },
+            &finish_prompt(system_prompt),
+            &input,
+            on_token,
+            streaming_enabled(),
+        )
+        .await
+    })
+    .await
+}
+
+// Conventions from recent history, unless `learn_style = false`. Read once
+// per run, a few new commits don't change the style.
+fn learned_style() -> Option<&'static CommitStyle> {
+    static STYLE: OnceCell<Option<CommitStyle>> = OnceCell::new();
+    STYLE
+        .get_or_init(|| {
+            if GIT_AI_CONFIG.learn_style == Some(false) {
+                return None;
+            }
+            CommitStyle::infer()
+        })
+        .as_ref()
+}
+
+// The JSON contract always renders "type(scope): subject", so it is only
+// used (`structured_commits`, on by default) where history looks like that:
+// Conventional Commits without a ticket prefix in front
+pub fn structured_output_enabled() -> bool {
+    GIT_AI_CONFIG.structured_commits != Some(false)
+        && learned_style().is_none_or(|style| style.conventional && style.ticket_prefix.is_none())
+}
+
+// Longest header the structured output may have: the repository's own
+// longest subject when that is over the default. Shorter histories keep the
+// default so a slightly longer header isn't sent back for repair.
+pub fn header_limit() -> usize {
+    learned_style().map_or(MAX_HEADER_LEN, |style| {
+        style.max_subject_len.max(MAX_HEADER_LEN)
+    })
+}
+
+// Rewrite `current` for an amended commit whose combined diff is `diff`.
+// Oversized diffs are cut to the budget, the current message carries the
+// intent of what was cut.
+pub async fn amend_commit_message(
+    diff: &str,
+    current: &str,
+    on_token: TokenSink<'_>,
+) -> Result<String, LLMError> {
+    let diff = truncate_to_budget(diff, diff_budget());
+    let (system_prompt, input) =
+        prompt_templates::load("amend").render(&[("diff", diff.as_str()), ("message", current)]);
+    let raw = request(
+        CompletionKind::plain("amend"),
+        &system_prompt,
+        &input,
+        on_token,
+        streaming_enabled(),
+    )
+    .await?;
+    Ok(clean_plain_message(&raw))
+}
+
+// Run a named prompt template (see prompt_templates), e.g. "ignore" or "init"
+pub async fn complete_template(
+    name: &str,
+    vars: &[(&str, &str)],
+    on_token: TokenSink<'_>,
+) -> Result<String, LLMError> {
+    let (system_prompt, input) = prompt_templates::load(name).render(vars);
+    request(
+        CompletionKind::plain(name),
+        &system_prompt,
+        &input,
+        on_token,
+        streaming_enabled(),
+    )
+    .await
+}
+
+fn streaming_enabled() -> bool {
+    GIT_AI_CONFIG.stream != Some(false)
+}
+
+// Tokens left for the diff once the prompt and the answer are accounted for
+fn diff_budget() -> usize {
+    let settings = backend().settings();
+    settings
+        .context_tokens
+        .saturating_sub(settings.max_tokens as usize + PROMPT_OVERHEAD_TOKENS)
+        .max(MIN_CHUNK_TOKENS)
+}
+
+// Map step: summarize every chunk, a few requests at a time
+async fn summarize_chunks(chunks: Vec<String>) -> Result<Vec<String>, LLMError> {
+    let semaphore = Arc::new(Semaphore::new(SUMMARY_CONCURRENCY));
+    let template = prompt_templates::load("summarize-chunk");
+    let mut tasks = JoinSet::new();
+    let mut summaries = vec![String::new(); chunks.len()];
+
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let (system_prompt, input) = template.render(&[("diff", chunk.as_str())]);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let summary = with_retries(
+                CompletionKind::plain("summarize-chunk"),
+                &system_prompt,
+                &input,
+                &mut |_| {},
+                false,
+            )
+            .await;
+            (index, summary)
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (index, summary) = joined.expect("Summary task panicked");
+        summaries[index] = summary?;
+    }
+
+    Ok(summaries)
+}
+
+// One backend call; Ctrl-C aborts it, including any backoff wait
+async fn request(
+    kind: CompletionKind<'_>,
+    system_prompt: &str,
+    input: &str,
+    on_token: TokenSink<'_>,
+    stream: bool,
+) -> Result<String, LLMError> {
+    cancellable(with_retries(kind, system_prompt, input, on_token, stream)).await
+}
+
+// Retry transient errors; half-printed output can't be taken back, so only
+// clean failures are retried
+async fn with_retries(
+    kind: CompletionKind<'_>,
+    system_prompt: &str,
+    input: &str,
+    on_token: TokenSink<'_>,
+    stream: bool,
+) -> Result<String, LLMError> {
+    let backend = backend();
+    let model = &backend.settings().model;
+    let cache_key = llm_cache::key(backend.name(), model, system_prompt, input);
+    if let Some(cached) = llm_cache::get(&cache_key) {
+        FROM_CACHE.store(true, Ordering::Relaxed);
+        on_token(&cached);
+        return Ok(cached);
+    }
+
+    let mut attempt = 0;
+    loop {
+        llm_usage::check_budget()?;
+
+        let mut streamed = false;
+        let started = Instant::now();
+        let (result, tokens) = llm_usage::measure(async {
+            if stream {
+                backend
+                    .complete_stream(kind, system_prompt, input, &mut |token| {
+                        streamed = true;
+                        on_token(token);
+                    })
+                    .await
+            } else {
+                backend.complete(kind, system_prompt, input).await
+            }
+        })
+        .await;
+
+        match result {
+            Ok(message) => {
+                if !stream {
+                    on_token(&message);
+                }
+                let (name, answered_model) = backend.answered_by();
+                llm_usage::record(
+                    name,
+                    answered_model,
+                    tokens,
+                    system_prompt,
+                    input,
+                    &message,
+                    started.elapsed(),
+                );
+                FROM_CACHE.store(false, Ordering::Relaxed);
+                // A fallback answer would be replayed long after the primary recovers
+                if !backend.fell_back() {
+                    llm_cache::put(&cache_key, backend.name(), model, &message);
+                }
+                return Ok(message);
+            }
+            Err(err) if !streamed => match retry_delay(attempt, &err) {
+                Some(delay) => {
+                    attempt += 1;
+                    println!(
+                        "{}",
+                        format!(
+                            "⏳ {}. Retrying in {:.1}s ({}/{})...",
+                            err,
+                            delay.as_secs_f32(),
+                            attempt,
+                            max_retries()
+                        )
+                        .yellow()
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                None => return Err(err),
+            },
+            Err(err) => return Err(err),
         }
-        LLMBackend::NoLLM => Err(Box::<dyn Error + Send + Sync>::from("no_llm is set")),
     }
 }
 
+fn max_retries() -> u32 {
+    GIT_AI_CONFIG.max_retries.unwrap_or(3)
+}
+
+// Exponential backoff with jitter, or the server's Retry-After when it sent one
+fn retry_delay(attempt: u32, err: &LLMError) -> Option<Duration> {
+    if !err.is_retryable() || attempt >= max_retries() {
+        return None;
+    }
+
+    if let Some(retry_after) = err.retry_after() {
+        // Don't sit in the terminal for minutes waiting on a quota window
+        return (retry_after <= MAX_RETRY_WAIT).then_some(retry_after);
+    }
+
+    let backoff = Duration::from_millis(500 * 2u64.pow(attempt)).min(MAX_RETRY_WAIT);
+    let jitter = Duration::from_millis(rand::rng().random_range(0..250));
+    Some(backoff + jitter)
+}
+
+// Race a backend call against Ctrl-C so the request is dropped cleanly
+async fn cancellable<T>(call: impl Future<Output = Result<T, LLMError>>) -> Result<T, LLMError> {
+    let mut presses = ctrl_c_presses();
+    IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    let result = tokio::select! {
+        result = call => result,
+        _ = presses.changed() => Err(LLMError::Cancelled),
+    };
+    IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    result
+}
+
+// One process-wide Ctrl-C listener. Listening replaces the default handler,
+// so outside a backend call it quits like before; during one it only counts
+// the press for `cancellable`.
+fn ctrl_c_presses() -> watch::Receiver<u64> {
+    static PRESSES: OnceCell<watch::Sender<u64>> = OnceCell::new();
+    PRESSES
+        .get_or_init(|| {
+            tokio::spawn(async {
+                while tokio::signal::ctrl_c().await.is_ok() {
+                    if IN_FLIGHT.load(Ordering::SeqCst) == 0 {
+                        println!();
+                        std::process::exit(130);
+                    }
+                    if let Some(presses) = PRESSES.get() {
+                        presses.send_modify(|count| *count += 1);
+                    }
+                }
+            });
+            watch::channel(0).0
+        })
+        .subscribe()
+}
+
+// Offline suggestion from the staged diff (renames `-M` included), used by
+// the commit paths that don't ask a model
 pub fn suggest_commit_message() -> String {
-    let options = vec![
-        "[git-ai] cleaned config spaghetti 🍝",
-        "[git-ai] squashed timestamps like a boss 🕒",
-        "[git-ai] localhost chaos managed 🚀",
-        "[git-ai] peace restored to your diffs ✌️",
-        "[git-ai] tiny tweaks, big vibes 🎯",
-        "[git-ai] silenced noisy paths 🔇",
-    ];
-
-    options.choose(&mut rand::rng()).unwrap().to_string()
+    let output = Command::new("git")
+        .args(["diff", "--cached", "-M"])
+        .output()
+        .expect("Failed to read staged diff");
+
+    diff_analysis::describe(&String::from_utf8_lossy(&output.stdout)).render()
 }
@@ -0,0 +1,87 @@
+use crate::commands::hunk::split_diff_into_hunks;
+
+// Rough token estimate, ~4 characters per token for code and English
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+// Pack a unified diff into chunks of at most `budget` tokens, keeping whole
+// files together where possible and falling back to single hunks
+pub fn chunk_diff(diff: &str, budget: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for piece in diff_pieces(diff, budget) {
+        if !current.is_empty() && estimate_tokens(&current) + estimate_tokens(&piece) > budget {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(&piece);
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+fn diff_pieces(diff: &str, budget: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+
+    for file in split_by_file(diff) {
+        if estimate_tokens(&file) <= budget {
+            pieces.push(file);
+            continue;
+        }
+
+        let (header, hunks) = split_diff_into_hunks(&file);
+        if hunks.is_empty() {
+            pieces.push(truncate_to_budget(&file, budget));
+        }
+        for hunk in hunks {
+            pieces.push(truncate_to_budget(&format!("{}{}", header, hunk), budget));
+        }
+    }
+
+    pieces
+}
+
+pub fn split_by_file(diff: &str) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut current = String::new();
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git") && !current.is_empty() {
+            files.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+
+    if !current.is_empty() {
+        files.push(current);
+    }
+
+    files
+}
+
+// Lockfile bumps and generated code can blow past any budget on their own
+pub fn truncate_to_budget(text: &str, budget: usize) -> String {
+    if estimate_tokens(text) <= budget {
+        return text.to_string();
+    }
+
+    let mut kept = String::new();
+    let mut dropped = 0;
+    for line in text.lines() {
+        if dropped == 0 && estimate_tokens(&kept) + estimate_tokens(line) < budget {
+            kept.push_str(line);
+            kept.push('\n');
+        } else {
+            dropped += 1;
+        }
+    }
+
+    kept.push_str(&format!("... ({} more lines truncated)\n", dropped));
+    kept
+}
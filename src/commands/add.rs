@@ -1,4 +1,4 @@
-use crate::commit::commit_changes;
+use crate::commit::{commit_changes, CommitOptions};
 use crate::config::GIT_AI_CONFIG;
 use crate::utils::{detect_language, get_combined_ignores, should_ignore_file};
 use colored::*;
@@ -21,8 +21,11 @@ pub async fn add_files(all: bool, files: Vec<String>) {
     }
 
     if is_added && GIT_AI_CONFIG.auto_commit == Some(true) {
-        commit_changes(false, false, GIT_AI_CONFIG.ai_enabled == Some(true)).await;
-        return;
+        commit_changes(CommitOptions {
+            ai: GIT_AI_CONFIG.ai_enabled == Some(true),
+            ..Default::default()
+        })
+        .await;
     }
 }
 
@@ -85,7 +88,7 @@ fn add_all_files(auto_ignores: &[String]) -> bool {
             .expect("Failed to git add");
         println!("✅ Staged: {}", file.bright_green());
     }
-    return added;
+    added
 }
 
 fn add_specific_files(files: &[String], auto_ignores: &[String]) -> bool {
@@ -117,7 +120,7 @@ fn add_specific_files(files: &[String], auto_ignores: &[String]) -> bool {
 
         println!("✅ Staged: {}", file.bright_green());
     }
-    return is_added;
+    is_added
 }
 
 // 🔥 Interactive add
@@ -171,7 +174,7 @@ fn interactive_add(auto_ignores: &[String]) -> bool {
                 .expect("Failed to git add");
             println!("✅ Staged: {}", file.bright_green());
         }
-        return true;
+        true
     } else {
         let picks: Vec<&str> = selection.split(' ').collect();
         let mut added = false;
@@ -193,6 +196,6 @@ fn interactive_add(auto_ignores: &[String]) -> bool {
                 println!("❌ Invalid input: {}", pick);
             }
         }
-        return added;
+        added
     }
 }
@@ -0,0 +1,22 @@
+use crate::llm_cache;
+use colored::*;
+
+pub fn cache_handler(command: &str) {
+    match command {
+        "clear" => clear_cache(),
+        _ => {
+            println!("{}", "❌ Invalid cache command.".red());
+        }
+    }
+}
+
+// Remove every cached AI response for this repository
+fn clear_cache() {
+    if llm_cache::cache_dir().is_none() {
+        println!("{}", "❌ Not inside a git repository.".red());
+        return;
+    }
+
+    let removed = llm_cache::clear();
+    println!("🧹 Cleared {} cached AI responses.", removed);
+}
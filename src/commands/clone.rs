@@ -82,7 +82,10 @@ fn run_git_clone(url: &str) -> bool {
 
 // Get repo name
 fn extract_repo_name(url: &str) -> String {
-    url.split('/').last().unwrap_or("repo").replace(".git", "")
+    url.split('/')
+        .next_back()
+        .unwrap_or("repo")
+        .replace(".git", "")
 }
 
 // Scan project after clone
@@ -1,35 +1,76 @@
-use crate::ai::generate_commit_message;
 use crate::ai::suggest_commit_message;
+use crate::ai::{
+    amend_commit_message, answered_by, backend_label, generate_structured_commit_message,
+    stream_commit_message, structured_output_enabled,
+};
+use crate::commands::split::commit_split;
+use crate::commit_lint::{comment_char, lint, strip_comments};
+use crate::commit_message::CANDIDATE_STYLES;
+use crate::commit_trailers::decorate;
 use crate::config::GIT_AI_CONFIG;
+use crate::diff_analysis::describe;
+use crate::llms::error::LLMError;
 use crate::push::push_changes;
-use crate::utils::has_staged_changes;
+use crate::redact::redact;
+use crate::utils::{has_staged_changes, open_in_editor, show_in_pager};
 
 use colored::*;
+use std::fs;
 use std::io::{self, Write};
-use std::process::Command;
+use std::path::Path;
+use std::process::{Command, Stdio};
 
-pub async fn commit_changes(amend: bool, reword: bool, ai: bool) {
+#[derive(Debug, Default)]
+pub struct CommitOptions {
+    pub amend: bool,
+    pub reword: bool,
+    pub ai: bool,
+    // Ask for several alternatives and pick one
+    pub candidates: Option<usize>,
+    // Non-interactive: commit the n-th candidate (1-based) without prompting
+    pub pick: Option<usize>,
+    pub show_redacted: bool,
+    // Propose several commits for the staged hunks, see split.rs
+    pub split: bool,
+}
+
+pub async fn commit_changes(options: CommitOptions) {
     let mut is_committed = false;
-    if !has_staged_changes() {
+    // Amending and rewording work on HEAD, staged changes are optional
+    if options.amend {
+        is_committed = commit_amend(&options).await;
+    } else if options.reword {
+        is_committed = commit_reword(&options).await;
+    } else if !has_staged_changes() {
         println!(
             "{}",
             "⚠️ No staged changes found. Please stage files first!".yellow()
         );
-    } else if amend {
-        is_committed = commit_amend().await;
-    } else if reword {
-        is_committed = commit_reword();
-    } else if ai {
-        is_committed = commit_with_ai().await;
+    } else if options.split {
+        is_committed = commit_split(options.show_redacted).await;
+    } else if options.ai
+        || options.candidates.is_some()
+        || options.pick.is_some()
+        || options.show_redacted
+    {
+        is_committed = commit_with_ai(&options).await;
     } else {
         is_committed = normal_commit();
     }
 
+    // Scripts using --pick need to see the failure
+    if options.pick.is_some() && !is_committed {
+        std::process::exit(1);
+    }
+
     if is_committed {
         if GIT_AI_CONFIG.auto_push == Some(true) {
             push_changes();
             return;
         }
+        if options.pick.is_some() {
+            return;
+        }
         println!("successfully commit, do you want to push also (y/n)");
         let mut answer = String::new();
         std::io::stdin().read_line(&mut answer).unwrap();
@@ -40,8 +81,11 @@ pub async fn commit_changes(amend: bool, reword: bool, ai: bool) {
     }
 }
 
-async fn commit_with_ai() -> bool {
-    println!("{}", "🤖 Generating commit message with AI...".cyan());
+async fn commit_with_ai(options: &CommitOptions) -> bool {
+    println!(
+        "{}",
+        format!("🤖 Generating commit message with {}...", backend_label()).cyan()
+    );
 
     let output = Command::new("git")
         .arg("diff")
@@ -59,13 +103,23 @@ async fn commit_with_ai() -> bool {
         return false;
     }
 
-    match generate_commit_message(&diff_text).await {
+    let Some(diff_text) = redact_diff(&diff_text, options.show_redacted) else {
+        return false;
+    };
+
+    if options.candidates.is_some() || options.pick.is_some() {
+        // --pick alone still needs something to pick from
+        let count = options
+            .candidates
+            .unwrap_or(CANDIDATE_STYLES.len())
+            .max(options.pick.unwrap_or(1));
+        return commit_with_candidates(&diff_text, count, options.pick).await;
+    }
+
+    match ai_commit_message(&diff_text).await {
         Ok(suggested) => {
-            println!(
-                "\n✨ AI Suggested Commit Message: {}",
-                suggested.bright_magenta()
-            );
-            println!("Use this message? (y = yes, n = no, q = quit)");
+            report_lint(&suggested);
+            println!("Use this message? (y = yes, n = no, e = edit, q = quit)");
 
             let mut answer = String::new();
             io::stdout().flush().unwrap();
@@ -74,6 +128,9 @@ async fn commit_with_ai() -> bool {
 
             match answer.as_str() {
                 "y" => run_git_commit(&suggested),
+                "e" => edit_message(&suggested, STAGED_STAT)
+                    .map(|message| run_git_commit(&message))
+                    .unwrap_or(false),
                 "n" => {
                     println!("{}", "📝 Enter your custom commit message:".cyan());
                     let mut custom_message = String::new();
@@ -82,28 +139,324 @@ async fn commit_with_ai() -> bool {
                 }
                 "q" => {
                     println!("{}", "❌ Commit cancelled.".yellow());
-                    return false;
+                    false
                 }
                 _ => {
                     println!("{}", "❌ Invalid choice. Commit aborted.".red());
-                    return false;
+                    false
                 }
             }
         }
+        Err(LLMError::Cancelled) => {
+            println!("{}", "❌ AI request cancelled. Commit aborted.".yellow());
+            false
+        }
         Err(e) => {
-            println!("ai commit is not done because of: {}", e);
+            println!("{}", format!("❌ AI commit message failed: {}", e).red());
+            println!("{}", "💡 Falling back to a manual commit message.".cyan());
             normal_commit()
         }
     }
 }
 
+// Scrub secrets before the diff leaves the machine. With --show-redacted,
+// page through what will be sent and ask before sending it.
+pub fn redact_diff(diff: &str, preview: bool) -> Option<String> {
+    let redacted = redact(diff);
+    if redacted.count() > 0 {
+        println!(
+            "{}",
+            format!(
+                "🛡️ Redacted {} value(s) from the diff before sending: {}",
+                redacted.count(),
+                redacted.summary()
+            )
+            .yellow()
+        );
+    }
+
+    if !preview {
+        return Some(redacted.text);
+    }
+
+    println!("{}", "\n📤 Diff as it will be sent:\n".cyan());
+    show_in_pager(&redacted.text);
+    println!("\nSend this to {}? (y/n)", backend_label());
+
+    let mut answer = String::new();
+    io::stdout().flush().unwrap();
+    io::stdin().read_line(&mut answer).unwrap();
+    if answer.trim().to_lowercase() == "y" {
+        Some(redacted.text)
+    } else {
+        println!("{}", "❌ Nothing was sent. Commit aborted.".yellow());
+        None
+    }
+}
+
+async fn commit_with_candidates(diff: &str, count: usize, pick: Option<usize>) -> bool {
+    let mut candidates = Vec::new();
+
+    for index in 0..count {
+        let (label, instruction) = CANDIDATE_STYLES[index % CANDIDATE_STYLES.len()];
+        // Past the built-in styles, ask for another take so each request differs
+        let instruction = match index / CANDIDATE_STYLES.len() {
+            0 => instruction.to_string(),
+            take => format!(
+                "{} Offer an alternative wording (take {}).",
+                instruction,
+                take + 1
+            ),
+        };
+
+        println!("⏳ Candidate {}/{} ({})...", index + 1, count, label);
+        match quiet_commit_message(diff, Some(&instruction)).await {
+            Ok(message) => candidates.push((format!("{} · {}", label, answered_by()), message)),
+            Err(LLMError::Cancelled) => {
+                println!("{}", "❌ AI request cancelled. Commit aborted.".yellow());
+                return false;
+            }
+            Err(e) => println!(
+                "{}",
+                format!("⚠️ Candidate {} failed: {}", index + 1, e).yellow()
+            ),
+        }
+    }
+
+    if candidates.is_empty() {
+        println!("{}", "❌ No candidates could be generated.".red());
+        // --pick runs unattended, there is nobody to type a message
+        if pick.is_some() {
+            return false;
+        }
+        println!("{}", "💡 Falling back to a manual commit message.".cyan());
+        return normal_commit();
+    }
+
+    if let Some(pick) = pick {
+        return match candidates.get(pick.wrapping_sub(1)) {
+            Some((label, message)) => {
+                println!("✅ Picked candidate {} ({})", pick, label);
+                run_git_commit(message)
+            }
+            None => {
+                println!(
+                    "{}",
+                    format!(
+                        "❌ --pick {} is out of range, only {} candidates were generated.",
+                        pick,
+                        candidates.len()
+                    )
+                    .red()
+                );
+                false
+            }
+        };
+    }
+
+    println!("\n✨ AI Suggested Commit Messages:");
+    for (index, (label, message)) in candidates.iter().enumerate() {
+        println!(
+            "\n{} {}\n{}",
+            format!("{})", index + 1).bright_cyan(),
+            format!("[{}]", label).dimmed(),
+            message.bright_magenta()
+        );
+        report_lint(message);
+    }
+
+    println!(
+        "\nPick a message (1-{}), e<number> to edit it first, q = quit",
+        candidates.len()
+    );
+    let mut answer = String::new();
+    io::stdout().flush().unwrap();
+    io::stdin().read_line(&mut answer).unwrap();
+    let answer = answer.trim().to_lowercase();
+
+    if answer == "q" {
+        println!("{}", "❌ Commit cancelled.".yellow());
+        return false;
+    }
+
+    let (edit, number) = match answer.strip_prefix('e') {
+        Some(number) => (true, number),
+        None => (false, answer.as_str()),
+    };
+    match number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| candidates.get(n.wrapping_sub(1)))
+    {
+        Some((_, message)) if edit => edit_message(message, STAGED_STAT)
+            .map(|message| run_git_commit(&message))
+            .unwrap_or(false),
+        Some((_, message)) => run_git_commit(message),
+        None => {
+            println!("{}", "❌ Invalid choice. Commit aborted.".red());
+            false
+        }
+    }
+}
+
+// Candidate generation prints progress lines instead of streaming tokens
+pub async fn quiet_commit_message(diff: &str, style: Option<&str>) -> Result<String, LLMError> {
+    if !structured_output_enabled() {
+        return stream_commit_message(diff, style, &mut |_| {}).await;
+    }
+
+    Ok(generate_structured_commit_message(diff, style, &mut |_| {})
+        .await?
+        .render())
+}
+
+// Message for a plain `git commit` (prepare-commit-msg hook): from the AI
+// backend when there is one, the offline suggestion otherwise
+pub async fn hook_commit_message(diff: &str) -> String {
+    let diff = redact(diff).text;
+    match quiet_commit_message(&diff, None).await {
+        Ok(message) => message,
+        Err(LLMError::NoBackend) => suggest_commit_message(),
+        Err(e) => {
+            println!(
+                "{}",
+                format!(
+                    "⚠️ AI commit message failed ({}), using the offline one.",
+                    e
+                )
+                .yellow()
+            );
+            suggest_commit_message()
+        }
+    }
+}
+
+// Structured output streams the raw JSON dimmed and then shows the rendered
+// message; plain output streams the message itself
+async fn ai_commit_message(diff: &str) -> Result<String, LLMError> {
+    // Header goes out with the first token so progress and retry notes
+    // print on their own lines
+    let mut header_shown = false;
+
+    if !structured_output_enabled() {
+        let result = stream_commit_message(diff, None, &mut |token| {
+            if !header_shown {
+                print!("\n✨ AI Suggested Commit Message: ");
+                header_shown = true;
+            }
+            print!("{}", token.bright_magenta());
+            io::stdout().flush().unwrap();
+        })
+        .await;
+        println!();
+        if result.is_ok() {
+            report_provider();
+        }
+        return result;
+    }
+
+    let result = generate_structured_commit_message(diff, None, &mut |token| {
+        if !header_shown {
+            println!("{}", "\n📡 Model output:".dimmed());
+            header_shown = true;
+        }
+        print!("{}", token.dimmed());
+        io::stdout().flush().unwrap();
+    })
+    .await;
+    println!();
+
+    let message = result?.render();
+    println!(
+        "\n✨ AI Suggested Commit Message:\n{}\n",
+        message.bright_magenta()
+    );
+    report_provider();
+    Ok(message)
+}
+
+// `git` arguments for the diffstat shown under the message being edited
+const STAGED_STAT: &[&str] = &["diff", "--cached", "--stat"];
+// git's well-known empty tree, the "parent" of a root commit
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+// Open the message in the editor with the diffstat as comments below it.
+// Comments are stripped afterwards and an empty message aborts, like git.
+pub fn edit_message(message: &str, stat_args: &[&str]) -> Option<String> {
+    let path = Command::new("git")
+        .args(["rev-parse", "--git-path", "GIT_AI_EDITMSG"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    if path.is_empty() {
+        println!("{}", "❌ Not inside a git repository.".red());
+        return None;
+    }
+
+    let stat = Command::new("git")
+        .args(stat_args)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .unwrap_or_default();
+    let comment = comment_char(message);
+    let mut content = format!(
+        "{}\n\n{c} Edit the commit message above. Lines starting with '{c}' are ignored,\n{c} and an empty message aborts the commit.\n{c}\n",
+        message.trim(),
+        c = comment
+    );
+    for line in stat.lines() {
+        content.push_str(&format!("{} {}\n", comment, line.trim_end()));
+    }
+
+    let path = Path::new(&path);
+    if let Err(e) = fs::write(path, content) {
+        println!(
+            "{}",
+            format!("❌ Failed to write {}: {}", path.display(), e).red()
+        );
+        return None;
+    }
+    if !open_in_editor(path) {
+        println!(
+            "{}",
+            "❌ The editor exited with an error. Commit aborted.".red()
+        );
+        return None;
+    }
+
+    let edited = fs::read_to_string(path).unwrap_or_default();
+    let _ = fs::remove_file(path);
+    let message = strip_comments(&edited);
+    if message.is_empty() {
+        println!("{}", "❌ Empty commit message. Commit aborted.".yellow());
+        return None;
+    }
+    Some(message)
+}
+
+// Flag suggestions that break the `[lint]` rules before they are offered
+pub fn report_lint(message: &str) {
+    for problem in lint(message) {
+        println!("{}", format!("⚠️ Lint: {}", problem).yellow());
+    }
+}
+
+// With a fallback chain, say which step actually wrote the message
+fn report_provider() {
+    let provider = answered_by();
+    if provider != backend_label() {
+        println!("{}", format!("💡 Written by {}", provider).dimmed());
+    }
+}
+
 fn normal_commit() -> bool {
     let suggested = suggest_commit_message();
     println!(
         "\n✨ Suggested Commit Message: {}",
         suggested.bright_magenta()
     );
-    println!("Use this message? (y/n/q)");
+    println!("Use this message? (y/n/e = edit/q)");
 
     let mut answer = String::new();
     io::stdout().flush().unwrap();
@@ -112,6 +465,9 @@ fn normal_commit() -> bool {
 
     match answer.as_str() {
         "y" => run_git_commit(&suggested),
+        "e" => edit_message(&suggested, STAGED_STAT)
+            .map(|message| run_git_commit(&message))
+            .unwrap_or(false),
         "n" => {
             println!("{}", "📝 Enter your custom commit message:".cyan());
             let mut custom_message = String::new();
@@ -120,43 +476,36 @@ fn normal_commit() -> bool {
         }
         "q" => {
             println!("{}", "❌ Commit cancelled.".yellow());
-            return false;
+            false
         }
         _ => {
             println!("{}", "❌ Invalid choice. Commit aborted.".red());
-            return false;
+            false
         }
     }
 }
 
-fn run_git_commit(message: &str) -> bool {
+pub fn run_git_commit(message: &str) -> bool {
     let commit_status = Command::new("git")
         .arg("commit")
         .arg("-m")
-        .arg(message)
+        .arg(decorate(message))
         .status()
         .expect("Failed to run git commit");
 
     if commit_status.success() {
         println!("{}", "✅ Commit successful!".green());
-        return true;
+        true
     } else {
         println!("{}", "❌ Commit failed!".red());
-        return false;
+        false
     }
 }
 
-async fn commit_amend() -> bool {
+async fn commit_amend(options: &CommitOptions) -> bool {
     println!("📝 Preparing to amend last commit...");
 
-    let status = Command::new("git")
-        .arg("diff")
-        .arg("--cached")
-        .arg("--quiet")
-        .status()
-        .expect("Failed to check staged changes");
-
-    if status.success() {
+    if !has_staged_changes() {
         // Nothing staged, just reword
         println!("⚠️ No staged changes found.");
         println!("📝 Do you want to reword the last commit message? (y/n): ");
@@ -165,70 +514,184 @@ async fn commit_amend() -> bool {
         let answer = answer.trim().to_lowercase();
 
         if answer == "y" {
-            commit_reword()
+            commit_reword(options).await
         } else {
             println!("{}", "❌ Amend cancelled.".yellow());
-            return false;
+            false
         }
     } else {
         println!("✨ Staged changes found. Amending into last commit...");
 
-        let suggested = suggest_commit_message();
-        println!(
-            "\n✨ Suggested Commit Message: {}",
-            suggested.bright_magenta()
-        );
-        println!("Use this message? (y = yes, n = no, q = quit)");
+        match amended_message(options.ai, options.show_redacted).await {
+            Some(suggested) => offer_amend(&suggested),
+            None => false,
+        }
+    }
+}
 
-        let mut answer = String::new();
-        std::io::stdin().read_line(&mut answer).unwrap();
-        let answer = answer.trim().to_lowercase();
+async fn commit_reword(options: &CommitOptions) -> bool {
+    println!("📝 Rewording last commit...");
 
-        match answer.as_str() {
-            "y" => {
-                run_git_commit_amend(&suggested);
-                return true;
-            }
-            "n" => {
-                println!("{}", "📝 Enter your custom amend commit message:".cyan());
-                let mut custom_message = String::new();
-                std::io::stdin().read_line(&mut custom_message).unwrap();
-                run_git_commit_amend(custom_message.trim())
+    if options.ai {
+        return match amended_message(true, options.show_redacted).await {
+            Some(suggested) => offer_amend(&suggested),
+            None => false,
+        };
+    }
+
+    println!(
+        "{}",
+        "📝 Enter the new commit message (or e to edit the current one in your editor):".cyan()
+    );
+    let mut custom_message = String::new();
+    std::io::stdin().read_line(&mut custom_message).unwrap();
+    let mut custom_message = custom_message.trim().to_string();
+
+    if custom_message == "e" {
+        match edit_message(&head_message(), &amend_stat(&amend_base())) {
+            Some(message) => custom_message = message,
+            None => return false,
+        }
+    }
+
+    run_git_commit_amend(&custom_message)
+}
+
+fn offer_amend(suggested: &str) -> bool {
+    report_lint(suggested);
+    println!("Use this message? (y = yes, n = no, e = edit, q = quit)");
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).unwrap();
+    let answer = answer.trim().to_lowercase();
+
+    match answer.as_str() {
+        "y" => run_git_commit_amend(suggested),
+        "e" => edit_message(suggested, &amend_stat(&amend_base()))
+            .map(|message| run_git_commit_amend(&message))
+            .unwrap_or(false),
+        "n" => {
+            println!("{}", "📝 Enter your custom amend commit message:".cyan());
+            let mut custom_message = String::new();
+            std::io::stdin().read_line(&mut custom_message).unwrap();
+            run_git_commit_amend(custom_message.trim())
+        }
+        "q" => {
+            println!("{}", "❌ Commit amend cancelled.".yellow());
+            false
+        }
+        _ => {
+            println!("{}", "❌ Invalid choice. Aborting.".red());
+            false
+        }
+    }
+}
+
+// Message for the commit as it will be after amending, written from the
+// combined diff (HEAD's parent to the index) and the current message.
+// Trailers of the current message are carried over unchanged.
+async fn amended_message(ai: bool, show_redacted: bool) -> Option<String> {
+    let output = Command::new("git")
+        .args(["diff", "--cached", &amend_base()])
+        .output()
+        .expect("Failed to read combined diff");
+    let diff = String::from_utf8_lossy(&output.stdout).to_string();
+    let current = head_message();
+    let trailers = message_trailers(&current);
+
+    if ai {
+        println!(
+            "{}",
+            format!("🤖 Updating the commit message with {}...", backend_label()).cyan()
+        );
+        let diff = redact_diff(&diff, show_redacted)?;
+        match amend_commit_message(&diff, &redact(&current).text, &mut |_| {}).await {
+            Ok(message) => {
+                let message = keep_trailers(&message, &trailers);
+                println!(
+                    "\n✨ AI Suggested Commit Message:\n{}\n",
+                    message.bright_magenta()
+                );
+                report_provider();
+                return Some(message);
             }
-            "q" => {
-                println!("{}", "❌ Commit amend cancelled.".yellow());
-                return false;
+            Err(LLMError::Cancelled) => {
+                println!("{}", "❌ AI request cancelled. Amend aborted.".yellow());
+                return None;
             }
-            _ => {
-                println!("{}", "❌ Invalid choice. Aborting.".red());
-                return false;
+            Err(e) => {
+                println!("{}", format!("❌ AI commit message failed: {}", e).red());
+                println!("{}", "💡 Falling back to the offline suggestion.".cyan());
             }
         }
     }
+
+    let suggested = keep_trailers(&describe(&diff).render(), &trailers);
+    println!(
+        "\n✨ Suggested Commit Message: {}",
+        suggested.bright_magenta()
+    );
+    Some(suggested)
 }
 
-fn commit_reword() -> bool {
-    println!("📝 Rewording last commit...");
+// HEAD's parent, or the empty tree when HEAD is the root commit
+fn amend_base() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--verify", "-q", "HEAD~1"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| EMPTY_TREE.to_string())
+}
 
-    println!("{}", "📝 Enter the new commit message:".cyan());
-    let mut custom_message = String::new();
-    std::io::stdin().read_line(&mut custom_message).unwrap();
+fn amend_stat(base: &str) -> Vec<&str> {
+    vec!["diff", "--cached", "--stat", base]
+}
 
-    let commit_status = Command::new("git")
-        .arg("commit")
-        .arg("--amend")
-        .arg("-m")
-        .arg(custom_message.trim())
-        .status()
-        .expect("Failed to amend commit message");
+fn head_message() -> String {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%B"])
+        .output()
+        .expect("Failed to read last commit message");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
 
-    if commit_status.success() {
-        println!("{}", "✅ Commit message updated!".green());
-        return true;
-    } else {
-        println!("{}", "❌ Commit reword failed!".red());
-        return false;
+// Trailers as git parses them, e.g. "Signed-off-by: A <a@example.com>"
+pub fn message_trailers(message: &str) -> Vec<String> {
+    let Ok(mut child) = Command::new("git")
+        .args(["interpret-trailers", "--parse"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+    else {
+        return Vec::new();
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(message.as_bytes());
+    }
+    child
+        .wait_with_output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| line.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Put the original trailers back under a regenerated message, dropping
+// any copies the model echoed
+pub fn keep_trailers(message: &str, trailers: &[String]) -> String {
+    if trailers.is_empty() {
+        return message.to_string();
     }
+    let kept: Vec<&str> = message
+        .lines()
+        .filter(|line| !trailers.iter().any(|trailer| trailer == line.trim()))
+        .collect();
+    format!("{}\n\n{}", kept.join("\n").trim_end(), trailers.join("\n"))
 }
 
 fn run_git_commit_amend(message: &str) -> bool {
@@ -236,15 +699,15 @@ fn run_git_commit_amend(message: &str) -> bool {
         .arg("commit")
         .arg("--amend")
         .arg("-m")
-        .arg(message)
+        .arg(decorate(message))
         .status()
         .expect("Failed to amend commit");
 
     if commit_status.success() {
         println!("{}", "✅ Amend successful!".green());
-        return true;
+        true
     } else {
         println!("{}", "❌ Amend failed!".red());
-        return false;
+        false
     }
 }
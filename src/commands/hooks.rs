@@ -1,39 +1,147 @@
+use crate::commands::commit::hook_commit_message;
+use crate::commit_lint::lint;
 use crate::utils::{detect_language, get_auto_ignores};
 use colored::*;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-pub fn install_hook() {
-    let hook_path = ".git/hooks/pre-commit";
-    if Path::new(hook_path).exists() {
-        println!("Hook already exists. Overwrite manually if needed.");
-        return;
+// (hook name, script)
+const PRE_COMMIT_HOOK: (&str, &str) = ("pre-commit", "#!/bin/sh\ngit-ai precommit\n");
+
+// Opt-in, it rejects commits whose message breaks the [lint] rules
+const COMMIT_MSG_HOOK: (&str, &str) = ("commit-msg", "#!/bin/sh\ngit-ai lint-msg \"$1\"\n");
+
+// Opt-in, it runs the AI backend on every plain `git commit`
+const PREPARE_COMMIT_MSG_HOOK: (&str, &str) = (
+    "prepare-commit-msg",
+    "#!/bin/sh\ngit-ai hook prepare-commit-msg \"$1\" \"$2\" \"$3\"\n",
+);
+
+pub fn install_hook(prepare_commit_msg: bool, commit_msg: bool) {
+    let mut hooks = vec![PRE_COMMIT_HOOK];
+    if commit_msg {
+        hooks.push(COMMIT_MSG_HOOK);
+    }
+    if prepare_commit_msg {
+        hooks.push(PREPARE_COMMIT_MSG_HOOK);
     }
 
-    let script = r#"#!/bin/sh
-git-ai precommit
-"#;
-    fs::write(hook_path, script).expect("Failed to write hook file");
-    Command::new("chmod")
-        .arg("+x")
-        .arg(hook_path)
-        .status()
-        .expect("Failed to chmod hook");
-    println!("✅ Pre-commit hook installed!");
+    for (name, script) in hooks {
+        let hook_path = format!(".git/hooks/{}", name);
+        if Path::new(&hook_path).exists() {
+            println!(
+                "{} hook already exists. Overwrite manually if needed.",
+                name
+            );
+            continue;
+        }
+
+        fs::write(&hook_path, script).expect("Failed to write hook file");
+        Command::new("chmod")
+            .arg("+x")
+            .arg(&hook_path)
+            .status()
+            .expect("Failed to chmod hook");
+        println!("✅ {} hook installed!", name);
+    }
 }
 
 pub fn uninstall_hook() {
-    let hook_path = ".git/hooks/pre-commit";
-    if Path::new(hook_path).exists() {
-        fs::remove_file(hook_path).expect("Failed to remove hook");
-        println!("🗑️ Hook uninstalled.");
-    } else {
-        println!("No hook to uninstall.");
+    for (name, script) in [PRE_COMMIT_HOOK, COMMIT_MSG_HOOK, PREPARE_COMMIT_MSG_HOOK] {
+        let hook_path = format!(".git/hooks/{}", name);
+        // Leave hooks we didn't write alone
+        match fs::read_to_string(&hook_path) {
+            Ok(content) if content == script => {
+                fs::remove_file(&hook_path).expect("Failed to remove hook");
+                println!("🗑️ {} hook uninstalled.", name);
+            }
+            Ok(_) => println!("{} hook was not installed by git-ai, leaving it.", name),
+            Err(_) => println!("No {} hook to uninstall.", name),
+        }
+    }
+}
+
+// commit-msg hook: a non-zero exit makes git abort the commit
+pub fn run_lint_msg(path: &str) {
+    let message = match fs::read_to_string(path) {
+        Ok(message) => message,
+        Err(e) => {
+            println!("{}", format!("❌ Can't read {}: {}", path, e).red());
+            std::process::exit(1);
+        }
+    };
+
+    let problems = lint(&message);
+    if problems.is_empty() {
+        println!("{}", "✅ Commit message looks good.".green());
+        return;
+    }
+
+    println!("{}", "❌ Commit message doesn't pass lint:".red());
+    for problem in &problems {
+        println!("  • {}", problem);
+    }
+    std::process::exit(1);
+}
+
+// A hook that can't do its job must not block `git commit`: panics are
+// reported as a warning and the hook still exits 0
+fn never_block_commit() {
+    std::panic::set_hook(Box::new(|info| {
+        let reason = info
+            .payload()
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| info.payload().downcast_ref::<&str>().copied())
+            .unwrap_or("unexpected error");
+        println!(
+            "{}",
+            format!("⚠️ git-ai hook skipped ({}).", reason).yellow()
+        );
+    }));
+}
+
+// prepare-commit-msg hook: fill in a message for fresh commits only. Any
+// `source` means git already has one (-m, -F, a template, merge, squash,
+// amend), and commits made by git-ai itself always pass -m.
+pub async fn run_prepare_commit_msg(file: String, source: Option<String>) {
+    never_block_commit();
+    // A panic ends the task, not the process
+    let _ = tokio::spawn(async move { prepare_commit_msg(&file, source.as_deref()).await }).await;
+}
+
+async fn prepare_commit_msg(file: &str, source: Option<&str>) {
+    if source.is_some_and(|source| !source.is_empty()) {
+        return;
+    }
+
+    let output = Command::new("git")
+        .arg("diff")
+        .arg("--cached")
+        .output()
+        .expect("Failed to read staged diff");
+    let diff = String::from_utf8_lossy(&output.stdout);
+    if diff.trim().is_empty() {
+        return;
+    }
+
+    println!("{}", "🤖 git-ai is writing the commit message...".cyan());
+    let message = hook_commit_message(&diff).await;
+
+    // Keep git's own comment lines below the message
+    let existing = fs::read_to_string(file).unwrap_or_default();
+    if let Err(e) = fs::write(file, format!("{}\n{}", message, existing)) {
+        println!("{}", format!("⚠️ Can't write {}: {}", file, e).yellow());
     }
 }
 
 pub fn run_precommit() {
+    never_block_commit();
+    let _ = std::panic::catch_unwind(precommit);
+}
+
+fn precommit() {
     println!("{}", "🔒 git-ai precommit check starting...".cyan());
 
     let language = detect_language();
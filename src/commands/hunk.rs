@@ -83,11 +83,17 @@ pub fn split_diff_into_hunks(diff_text: &str) -> (String, Vec<String>) {
     let mut in_hunk = false;
 
     for line in diff_text.lines() {
-        if line.starts_with("diff --git") {
-            header.push_str(line);
-            header.push('\n');
-        } else if line.starts_with("index ") || line.starts_with("--- ") || line.starts_with("+++ ")
+        // Inside a hunk "--- x" is a removed "-- x" line, not a file header
+        if line.starts_with("diff --git")
+            || (!in_hunk
+                && (line.starts_with("index ")
+                    || line.starts_with("--- ")
+                    || line.starts_with("+++ ")))
         {
+            if in_hunk {
+                hunks.push(std::mem::take(&mut current_hunk));
+                in_hunk = false;
+            }
             header.push_str(line);
             header.push('\n');
         } else if line.starts_with("@@") {
@@ -112,8 +118,11 @@ pub fn split_diff_into_hunks(diff_text: &str) -> (String, Vec<String>) {
 }
 
 fn apply_hunk(_filename: &str, header: &str, hunk_text: &str) {
-    let patch_content = format!("{}{}", header, hunk_text);
+    apply_to_index(&format!("{}{}", header, hunk_text));
+}
 
+// `git apply --cached` a patch, true when it applied cleanly
+pub fn apply_to_index(patch_content: &str) -> bool {
     let mut patch_cmd = Command::new("git")
         .arg("apply")
         .arg("--cached")
@@ -128,5 +137,8 @@ fn apply_hunk(_filename: &str, header: &str, hunk_text: &str) {
             .expect("Failed to write patch content");
     }
 
-    patch_cmd.wait().expect("Failed to wait on git apply");
+    patch_cmd
+        .wait()
+        .expect("Failed to wait on git apply")
+        .success()
 }
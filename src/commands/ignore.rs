@@ -1,4 +1,4 @@
-use crate::ai::generate_commit_message;
+use crate::ai::complete_template;
 use colored::*;
 use std::fs;
 use std::fs::OpenOptions;
@@ -25,11 +25,11 @@ async fn suggest_ignore_patterns(save: bool) {
 
     println!("📋 Found {} files to analyze.", file_list.len());
 
-    let prompt = build_ignore_prompt(&file_list);
+    let files = build_file_preview(&file_list);
 
     println!("{}", "🤖 Asking AI to suggest ignore patterns...".cyan());
 
-    match generate_commit_message(&prompt).await {
+    match complete_template("ignore", &[("files", &files)], &mut |_| {}).await {
         Ok(suggestions) => {
             println!(
                 "\n✨ AI Suggested Ignore Patterns:\n\n{}",
@@ -45,8 +45,11 @@ async fn suggest_ignore_patterns(save: bool) {
                 );
             }
         }
-        Err(_) => {
-            println!("{}", "❌ Failed to generate ignore suggestions.".red());
+        Err(e) => {
+            println!(
+                "{}",
+                format!("❌ Failed to generate ignore suggestions: {}", e).red()
+            );
         }
     }
 }
@@ -70,19 +73,14 @@ fn scan_project_files() -> Vec<String> {
     files
 }
 
-// Build prompt for LLM
-fn build_ignore_prompt(files: &[String]) -> String {
-    let preview = files
+// First 50 entries, one per line, for the "ignore" template's {files}
+fn build_file_preview(files: &[String]) -> String {
+    files
         .iter()
         .take(50)
         .cloned()
         .collect::<Vec<String>>()
-        .join("\n");
-
-    format!(
-        "Based on these project files:\n{}\n\nSuggest a .gitignore-style list of patterns that should be ignored to keep Git history clean. Focus on junk files, build artifacts, temporary files, environment files, node_modules, target, etc.",
-        preview
-    )
+        .join("\n")
 }
 
 fn save_ignore_file(content: &str) {
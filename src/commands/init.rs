@@ -5,7 +5,7 @@ use std::io::Write;
 use std::path::Path;
 use std::process::Command;
 
-use crate::ai::generate_project_scaffolding;
+use crate::ai::complete_template;
 
 #[derive(Debug, Clone)]
 pub enum ProjectLanguage {
@@ -55,7 +55,6 @@ async fn magic_init() {
 
     println!("📋 Found {} files to analyze.", file_list.len());
 
-    let system_prompt = "You are a Git repository initializer.\n\nGiven the following project files, Please:\n1. Guess the project type (e.g., Rust, Node.js, Python, etc.)\n2. Suggest a clean .gitignore\n3. Suggest a clean .git-ai-ignore\n4. Suggest a starter README.md\n\nReturn each section clearly titled.";
     let files_input = build_magic_init_files(&file_list);
 
     println!(
@@ -63,14 +62,19 @@ async fn magic_init() {
         "🤖 Asking AI to generate project scaffolding...".cyan()
     );
 
-    match generate_project_scaffolding(&system_prompt, &files_input).await {
+    println!(
+        "{}",
+        "✨ AI Suggested Project Scaffolding:\n".bright_magenta()
+    );
+    let result = complete_template("init", &[("files", &files_input)], &mut |token| {
+        print!("{}", token.bright_white());
+        io::stdout().flush().unwrap();
+    })
+    .await;
+    println!();
+
+    match result {
         Ok(ai_response) => {
-            println!(
-                "{}",
-                "✨ AI Suggested Project Scaffolding:\n".bright_magenta()
-            );
-            println!("{}", ai_response.bright_white());
-
             println!(
                 "{}",
                 "\n✅ Do you want to apply this magic setup? (y/n)".bright_cyan()
@@ -86,10 +90,14 @@ async fn magic_init() {
                 println!("{}", "❌ Magic init cancelled by user.".red());
             }
         }
-        Err(_) => {
+        Err(e) => {
             println!(
                 "{}",
-                "❌ AI failed to generate project scaffolding. Falling back to normal init.".red()
+                format!(
+                    "❌ AI failed to generate project scaffolding ({}). Falling back to normal init.",
+                    e
+                )
+                .red()
             );
             normal_init();
         }
@@ -115,16 +123,14 @@ fn scan_project_files() -> Vec<String> {
     files
 }
 
-// Build magic LLM files
+// First 50 entries, one per line, for the "init" template's {files}
 fn build_magic_init_files(files: &[String]) -> String {
-    let preview = files
+    files
         .iter()
         .take(50)
         .cloned()
         .collect::<Vec<String>>()
-        .join("\n");
-
-    format!("\ngenerate for these project files: {}", preview)
+        .join("\n")
 }
 
 // Save AI generated files
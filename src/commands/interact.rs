@@ -4,7 +4,7 @@ use crate::utils::show_in_pager;
 pub fn start_interactive_review(diff: String) {
     let (_header, hunks) = split_diff_into_hunks(&diff);
 
-    for (_, hunk) in hunks.iter().enumerate() {
+    for hunk in hunks.iter() {
         show_in_pager(hunk);
     }
 }
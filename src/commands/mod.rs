@@ -1,4 +1,5 @@
 pub mod add;
+pub mod cache;
 pub mod clone;
 pub mod commit;
 pub mod git_runner;
@@ -7,8 +8,14 @@ pub mod hunk;
 pub mod ignore;
 pub mod init;
 pub mod interact;
+pub mod models;
 pub mod pull;
 pub mod push;
+pub mod reword;
+pub mod split;
 pub mod staging;
 pub mod stash;
 pub mod status;
+pub mod style;
+pub mod templates;
+pub mod usage;
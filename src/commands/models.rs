@@ -0,0 +1,97 @@
+use crate::config::GIT_AI_CONFIG;
+use crate::llms::ollama::{is_installed, OllamaProvider};
+use crate::llms::LLMProvider;
+use colored::*;
+use indicatif::{ProgressBar, ProgressStyle};
+
+pub async fn models_handler(command: &str, name: Option<String>) {
+    // Model management talks to the local Ollama server whatever backend is active
+    let ollama = match OllamaProvider::new(GIT_AI_CONFIG.provider("ollama")) {
+        Ok(ollama) => ollama,
+        Err(e) => {
+            println!("{}", format!("❌ Failed to set up Ollama: {}", e).red());
+            return;
+        }
+    };
+
+    match command {
+        "list" => list_models(&ollama).await,
+        "pull" => pull_model(&ollama, name).await,
+        _ => {
+            println!("{}", "❌ Invalid models command.".red());
+        }
+    }
+}
+
+async fn list_models(ollama: &OllamaProvider) {
+    let configured = &ollama.settings().model;
+    let models = match ollama.list_models().await {
+        Ok(models) => models,
+        Err(e) => {
+            println!(
+                "{}",
+                format!(
+                    "❌ Could not reach Ollama at {}: {}",
+                    ollama.settings().base_url,
+                    e
+                )
+                .red()
+            );
+            return;
+        }
+    };
+
+    println!("📦 Models installed on {}:", ollama.settings().base_url);
+    for model in &models {
+        let marker = if is_installed(std::slice::from_ref(model), configured) {
+            "*".green().to_string()
+        } else {
+            " ".to_string()
+        };
+        println!(
+            " {} {:<40} {:>8.1} GB",
+            marker,
+            model.name,
+            model.size as f64 / 1e9
+        );
+    }
+
+    if !is_installed(&models, configured) {
+        println!(
+            "{}",
+            format!(
+                "⚠️ The configured model '{}' is not installed. Run `git-ai models pull {}`.",
+                configured, configured
+            )
+            .yellow()
+        );
+    }
+}
+
+async fn pull_model(ollama: &OllamaProvider, name: Option<String>) {
+    let name = name.unwrap_or_else(|| ollama.settings().model.clone());
+    println!("⬇️ Pulling {} from {}...", name, ollama.settings().base_url);
+
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::with_template("{msg} [{bar:30}] {bytes}/{total_bytes}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+
+    let result = ollama
+        .pull_model(&name, |progress| {
+            if let (Some(completed), Some(total)) = (progress.completed, progress.total) {
+                bar.set_length(total);
+                bar.set_position(completed);
+            }
+            bar.set_message(progress.status);
+        })
+        .await;
+    bar.finish_and_clear();
+
+    match result {
+        Ok(()) => println!("{}", format!("✅ {} is ready.", name).green()),
+        Err(e) => println!("{}", format!("❌ Failed to pull {}: {}", name, e).red()),
+    }
+}
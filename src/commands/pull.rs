@@ -25,7 +25,7 @@ pub fn smart_pull() {
     }
 
     if stashed {
-        println!("{}", "🛡️ Restoring stashed changes after pull...");
+        println!("🛡️ Restoring stashed changes after pull...");
         pop_stash();
     }
 
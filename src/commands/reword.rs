@@ -0,0 +1,391 @@
+use crate::commands::commit::{
+    edit_message, keep_trailers, message_trailers, quiet_commit_message, redact_diff, report_lint,
+};
+use crate::config::GIT_AI_CONFIG;
+use crate::diff_analysis::describe;
+use crate::llms::error::LLMError;
+use colored::*;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use terminal_size::{terminal_size, Width};
+
+const DEFAULT_PROTECTED_BRANCHES: [&str; 2] = ["main", "master"];
+
+struct Rewrite {
+    sha: String,
+    old: String,
+    // None when the commit keeps its message
+    new: Option<String>,
+}
+
+// `git-ai reword <rev-range>`: new messages for past commits on the current
+// branch, reviewed one by one, applied with a scripted `git rebase -i`
+pub async fn reword_commits(range: &str, force: bool) {
+    // A bare revision means everything after it, like `git rebase -i`
+    let range = if range.contains("..") {
+        range.to_string()
+    } else {
+        format!("{}..HEAD", range)
+    };
+
+    let Some(commits) = selected_commits(&range) else {
+        return;
+    };
+    let Some(pushed_to) = check_pushed(&commits[0], force) else {
+        return;
+    };
+
+    let mut rewrites = Vec::new();
+    for (index, sha) in commits.iter().enumerate() {
+        let old = git_output(&["log", "-1", "--format=%B", sha]).unwrap_or_default();
+        println!(
+            "⏳ Commit {}/{} {} {}",
+            index + 1,
+            commits.len(),
+            short(sha).bright_blue(),
+            old.lines().next().unwrap_or_default()
+        );
+        let Some(new) = improved_message(sha, &old).await else {
+            return;
+        };
+        rewrites.push(Rewrite {
+            sha: sha.clone(),
+            old,
+            new: Some(new),
+        });
+    }
+
+    if !review(&mut rewrites) {
+        return;
+    }
+    let changed = rewrites
+        .iter()
+        .filter(|rewrite| rewrite.new.is_some())
+        .count();
+    if changed == 0 {
+        println!(
+            "{}",
+            "👌 No messages changed, history left as it is.".yellow()
+        );
+        return;
+    }
+
+    if run_rebase(&rewrites) {
+        println!(
+            "{}",
+            format!(
+                "✅ Reworded {} commit(s). The previous tip is saved as ORIG_HEAD.",
+                changed
+            )
+            .green()
+        );
+        if !pushed_to.is_empty() {
+            println!(
+                "{}",
+                format!(
+                    "💡 These commits were on {}, publish the rewrite with `git push --force-with-lease`.",
+                    pushed_to.join(", ")
+                )
+                .cyan()
+            );
+        }
+    }
+}
+
+// Oldest first. Only linear history ending at HEAD can be rebased in place.
+fn selected_commits(range: &str) -> Option<Vec<String>> {
+    let Some(list) = git_output(&["rev-list", "--reverse", range]) else {
+        println!(
+            "{}",
+            format!("❌ Invalid revision range '{}'.", range).red()
+        );
+        return None;
+    };
+    let commits: Vec<String> = list.lines().map(|sha| sha.to_string()).collect();
+
+    if commits.is_empty() {
+        println!("{}", format!("⚠️ No commits in {}.", range).yellow());
+        return None;
+    }
+    if git_output(&["rev-parse", "HEAD"]).as_deref() != commits.last().map(|sha| sha.as_str()) {
+        println!(
+            "{}",
+            "❌ The range has to end at HEAD, check out the branch to reword first.".red()
+        );
+        return None;
+    }
+    if git_output(&["rev-list", "--merges", range]).is_some_and(|merges| !merges.is_empty()) {
+        println!(
+            "{}",
+            "❌ The range contains merge commits, which can't be reworded this way.".red()
+        );
+        return None;
+    }
+    if git_output(&["status", "--porcelain", "--untracked-files=no"])
+        .is_some_and(|status| !status.is_empty())
+    {
+        println!(
+            "{}",
+            "❌ Commit or stash your changes first, rewording rebases the branch.".red()
+        );
+        return None;
+    }
+
+    Some(commits)
+}
+
+// Remote branches that already have the oldest selected commit. Rewriting
+// what a protected branch (`protected_branches`, main/master by default)
+// has published needs --force.
+fn check_pushed(oldest: &str, force: bool) -> Option<Vec<String>> {
+    let pushed_to: Vec<String> = git_output(&[
+        "branch",
+        "-r",
+        "--contains",
+        oldest,
+        "--format=%(refname:short)",
+    ])
+    .unwrap_or_default()
+    .lines()
+    .map(|branch| branch.trim().to_string())
+    .filter(|branch| !branch.is_empty() && !branch.ends_with("/HEAD"))
+    .collect();
+
+    let protected: Vec<&String> = pushed_to
+        .iter()
+        .filter(|branch| {
+            let name = branch
+                .split_once('/')
+                .map_or(branch.as_str(), |(_, name)| name);
+            match &GIT_AI_CONFIG.protected_branches {
+                Some(protected) => protected.iter().any(|protected| protected == name),
+                None => DEFAULT_PROTECTED_BRANCHES.contains(&name),
+            }
+        })
+        .collect();
+
+    if !protected.is_empty() && !force {
+        println!(
+            "{}",
+            format!(
+                "❌ These commits are already on protected branch {}. Rewording would rewrite published history; pass --force if you really mean it.",
+                protected
+                    .iter()
+                    .map(|branch| branch.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .red()
+        );
+        return None;
+    }
+    Some(pushed_to)
+}
+
+// From the commit's own diff, like a fresh commit; the offline suggestion
+// when there is no backend. Trailers of the old message are kept.
+async fn improved_message(sha: &str, old: &str) -> Option<String> {
+    let diff = git_output(&["show", "--format=", sha]).unwrap_or_default();
+
+    let redacted = redact_diff(&diff, false).unwrap_or_default();
+
+    let message = match quiet_commit_message(&redacted, None).await {
+        Ok(message) => message,
+        Err(LLMError::Cancelled) => {
+            println!(
+                "{}",
+                "❌ AI request cancelled. Nothing was reworded.".yellow()
+            );
+            return None;
+        }
+        Err(LLMError::NoBackend) => describe(&diff).render(),
+        Err(e) => {
+            println!(
+                "{}",
+                format!("⚠️ AI message failed ({}), using the offline one.", e).yellow()
+            );
+            describe(&diff).render()
+        }
+    };
+
+    Some(keep_trailers(&message, &message_trailers(old)))
+}
+
+// Accept, edit or skip each proposal. False when the user quits.
+fn review(rewrites: &mut [Rewrite]) -> bool {
+    let total = rewrites.len();
+    let mut accept_rest = false;
+
+    for (index, rewrite) in rewrites.iter_mut().enumerate() {
+        let Some(new) = rewrite.new.clone() else {
+            continue;
+        };
+        if accept_rest {
+            continue;
+        }
+
+        println!(
+            "\n{} {}",
+            format!("{}/{}", index + 1, total).bright_cyan(),
+            short(&rewrite.sha).bright_blue()
+        );
+        print_side_by_side(&rewrite.old, &new);
+        report_lint(&new);
+        println!("a = accept, e = edit, s = skip, A = accept all remaining, q = quit");
+
+        let mut answer = String::new();
+        io::stdout().flush().unwrap();
+        io::stdin().read_line(&mut answer).unwrap();
+
+        match answer.trim() {
+            "a" => {}
+            "A" => accept_rest = true,
+            "e" => {
+                rewrite.new = edit_message(&new, &["show", "--stat", "--format=", &rewrite.sha]);
+            }
+            "s" => rewrite.new = None,
+            "q" => {
+                println!("{}", "❌ Reword cancelled, history left as it is.".yellow());
+                return false;
+            }
+            _ => {
+                println!("{}", "Invalid choice, skipping this commit.".yellow());
+                rewrite.new = None;
+            }
+        }
+    }
+
+    true
+}
+
+fn print_side_by_side(old: &str, new: &str) {
+    let width = terminal_size()
+        .map(|(Width(w), _)| w as usize)
+        .unwrap_or(100);
+    let column = (width.saturating_sub(3) / 2).max(20);
+
+    let old_lines = wrap_column(old, column);
+    let new_lines = wrap_column(new, column);
+    println!(
+        "{:<column$} │ {}",
+        "Old".dimmed(),
+        "New".green(),
+        column = column
+    );
+    for row in 0..old_lines.len().max(new_lines.len()) {
+        let left = old_lines.get(row).map(|line| line.as_str()).unwrap_or("");
+        let right = new_lines.get(row).map(|line| line.as_str()).unwrap_or("");
+        println!(
+            "{:<column$} │ {}",
+            left,
+            right.bright_magenta(),
+            column = column
+        );
+    }
+}
+
+// Hard wrap at `width` characters so both columns line up
+fn wrap_column(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            lines.push(String::new());
+        }
+        for piece in chars.chunks(width) {
+            lines.push(piece.iter().collect());
+        }
+    }
+    lines
+}
+
+// `git rebase -i` with our own todo list: every commit is picked again and
+// the reworded ones get an `exec git commit --amend -F <message file>`.
+// Messages are not decorated: the current branch ticket and co-authors say
+// nothing about past commits, which keep their own trailers.
+fn run_rebase(rewrites: &[Rewrite]) -> bool {
+    let Some(work_dir) = git_output(&["rev-parse", "--git-path", "git-ai-reword"]) else {
+        return false;
+    };
+    let work_dir = PathBuf::from(work_dir);
+    if let Err(e) = fs::create_dir_all(&work_dir) {
+        println!(
+            "{}",
+            format!("❌ Failed to prepare the rebase: {}", e).red()
+        );
+        return false;
+    }
+    // Relative git paths would break once the rebase changes directory
+    let work_dir = fs::canonicalize(&work_dir).unwrap_or(work_dir);
+
+    let mut todo = String::new();
+    for (index, rewrite) in rewrites.iter().enumerate() {
+        todo.push_str(&format!("pick {}\n", rewrite.sha));
+        if let Some(message) = &rewrite.new {
+            let message_path = work_dir.join(format!("{}.txt", index));
+            if fs::write(&message_path, format!("{}\n", message)).is_err() {
+                println!("{}", "❌ Failed to write the new messages.".red());
+                return false;
+            }
+            todo.push_str(&format!(
+                "exec git commit --amend --allow-empty --no-verify -q -F {}\n",
+                shell_quote(&message_path.to_string_lossy())
+            ));
+        }
+    }
+    let todo_path = work_dir.join("todo");
+    if fs::write(&todo_path, todo).is_err() {
+        println!("{}", "❌ Failed to write the rebase todo list.".red());
+        return false;
+    }
+
+    let oldest = &rewrites[0].sha;
+    let mut rebase = Command::new("git");
+    rebase
+        .args(["rebase", "-i", "--quiet"])
+        .env(
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+        )
+        .env("GIT_EDITOR", "true");
+    match git_output(&["rev-parse", "--verify", "-q", &format!("{}^", oldest)]) {
+        Some(parent) => rebase.arg(parent),
+        None => rebase.arg("--root"),
+    };
+    let success = rebase
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+
+    let _ = fs::remove_dir_all(&work_dir);
+    if !success {
+        Command::new("git")
+            .args(["rebase", "--abort"])
+            .status()
+            .ok();
+        println!(
+            "{}",
+            "❌ The rebase failed and was aborted, history is unchanged.".red()
+        );
+    }
+    success
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+fn git_output(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,467 @@
+use crate::ai::complete_template;
+use crate::chunking::split_by_file;
+use crate::commands::commit::{redact_diff, run_git_commit};
+use crate::commands::hunk::{apply_to_index, split_diff_into_hunks};
+use crate::commit_lint::{comment_char, strip_comments};
+use crate::commit_message::{clean_plain_message, extract_json_object};
+use crate::diff_analysis::{describe, file_group};
+use crate::llms::error::LLMError;
+use crate::utils::open_in_editor;
+use colored::*;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+
+// Hunks are cut to this many lines in the prompt, the header tells the story
+const MAX_PROMPT_HUNK_LINES: usize = 60;
+
+// One hunk of the staged diff, or a whole file section for changes without
+// hunks (binary files, pure renames, mode changes)
+struct Hunk {
+    file: String,
+    header: String,
+    body: String,
+}
+
+impl Hunk {
+    fn label(&self) -> String {
+        let location = self.body.lines().next().unwrap_or("(whole file)");
+        format!("{}  {}", self.file, location)
+    }
+}
+
+struct Group {
+    message: String,
+    // 0-based indexes into the hunk list
+    hunks: Vec<usize>,
+}
+
+// `git-ai commit --split`: propose commits for the staged hunks, let the
+// user approve or edit the plan, then commit the groups one by one
+pub async fn commit_split(show_redacted: bool) -> bool {
+    let output = Command::new("git")
+        .args(["diff", "--cached", "--binary"])
+        .output()
+        .expect("Failed to read staged diff");
+    let diff = String::from_utf8_lossy(&output.stdout).to_string();
+
+    let hunks = staged_hunks(&diff);
+    if hunks.len() < 2 {
+        println!(
+            "{}",
+            "⚠️ Only one staged hunk, nothing to split. Use git-ai commit instead.".yellow()
+        );
+        return false;
+    }
+
+    let Some(mut groups) = propose_groups(&hunks, show_redacted).await else {
+        return false;
+    };
+
+    loop {
+        show_plan(&hunks, &groups);
+        println!("Commit this plan? (y = yes, e = edit plan, q = quit)");
+
+        let mut answer = String::new();
+        io::stdout().flush().unwrap();
+        io::stdin().read_line(&mut answer).unwrap();
+
+        match answer.trim().to_lowercase().as_str() {
+            "y" => return commit_groups(&diff, &hunks, &groups),
+            "e" => match edit_plan(&hunks, &groups) {
+                Some(edited) => groups = edited,
+                None => return false,
+            },
+            "q" => {
+                println!("{}", "❌ Split cancelled, nothing was committed.".yellow());
+                return false;
+            }
+            _ => {
+                println!("{}", "❌ Invalid choice. Commit aborted.".red());
+                return false;
+            }
+        }
+    }
+}
+
+fn staged_hunks(diff: &str) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+
+    for section in split_by_file(diff) {
+        let file = section
+            .lines()
+            .next()
+            .and_then(|line| line.rsplit_once(" b/"))
+            .map(|(_, path)| path.to_string())
+            .unwrap_or_default();
+        // Everything up to the first hunk, so new-file and rename lines survive
+        let header: String = section
+            .lines()
+            .take_while(|line| !line.starts_with("@@"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+
+        let (_, bodies) = split_diff_into_hunks(&section);
+        if bodies.is_empty() {
+            hunks.push(Hunk {
+                file,
+                header: section,
+                body: String::new(),
+            });
+            continue;
+        }
+        for body in bodies {
+            hunks.push(Hunk {
+                file: file.clone(),
+                header: header.clone(),
+                body,
+            });
+        }
+    }
+
+    hunks
+}
+
+// Model grouping when a backend is configured, file-based grouping otherwise
+async fn propose_groups(hunks: &[Hunk], show_redacted: bool) -> Option<Vec<Group>> {
+    let listing: String = hunks
+        .iter()
+        .enumerate()
+        .map(|(index, hunk)| {
+            let body: Vec<&str> = hunk.body.lines().take(MAX_PROMPT_HUNK_LINES).collect();
+            format!(
+                "### Hunk {}: {}\n{}\n",
+                index + 1,
+                hunk.file,
+                body.join("\n")
+            )
+        })
+        .collect();
+    let listing = redact_diff(&listing, show_redacted)?;
+
+    println!("{}", "🧩 Grouping staged hunks into commits...".cyan());
+    match complete_template("split", &[("hunks", listing.as_str())], &mut |_| {}).await {
+        Ok(raw) => match parse_groups(&raw, hunks) {
+            Ok(groups) => return Some(groups),
+            Err(reason) => println!(
+                "{}",
+                format!(
+                    "⚠️ Could not use the proposed split ({}), grouping by file instead.",
+                    reason
+                )
+                .yellow()
+            ),
+        },
+        Err(LLMError::Cancelled) => {
+            println!("{}", "❌ AI request cancelled. Commit aborted.".yellow());
+            return None;
+        }
+        Err(LLMError::NoBackend) => {}
+        Err(e) => println!(
+            "{}",
+            format!("⚠️ AI grouping failed ({}), grouping by file instead.", e).yellow()
+        ),
+    }
+
+    Some(heuristic_groups(hunks))
+}
+
+// {"groups": [{"message": "...", "hunks": [1, 2]}]} with 1-based hunk ids.
+// Hunks the model forgot end up in a final group of their own.
+fn parse_groups(raw: &str, hunks: &[Hunk]) -> Result<Vec<Group>, String> {
+    let json = extract_json_object(raw).ok_or("no JSON object found in the output")?;
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("invalid JSON: {}", e))?;
+    let entries = value["groups"]
+        .as_array()
+        .ok_or("missing \"groups\" array")?;
+
+    let mut assigned = vec![false; hunks.len()];
+    let mut groups = Vec::new();
+    for entry in entries {
+        let ids: Vec<usize> = entry["hunks"]
+            .as_array()
+            .map(|ids| ids.iter().filter_map(|id| id.as_u64()).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| (id as usize).checked_sub(1))
+            .filter(|&index| index < hunks.len() && !std::mem::replace(&mut assigned[index], true))
+            .collect();
+        if ids.is_empty() {
+            continue;
+        }
+
+        let message = entry["message"]
+            .as_str()
+            .map(clean_plain_message)
+            .filter(|message| !message.is_empty())
+            .unwrap_or_else(|| describe(&group_patch(hunks, &ids)).render());
+        groups.push(Group {
+            message,
+            hunks: ids,
+        });
+    }
+    if groups.is_empty() {
+        return Err("no usable groups".to_string());
+    }
+
+    let leftover: Vec<usize> = (0..hunks.len()).filter(|&i| !assigned[i]).collect();
+    if !leftover.is_empty() {
+        groups.push(Group {
+            message: describe(&group_patch(hunks, &leftover)).render(),
+            hunks: leftover,
+        });
+    }
+
+    Ok(groups)
+}
+
+// Docs, tests, CI and dependency changes each get a commit, source changes
+// are grouped by top-level directory
+fn heuristic_groups(hunks: &[Hunk]) -> Vec<Group> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let key = file_group(&hunk.file);
+        match keys.iter().position(|existing| *existing == key) {
+            Some(position) => members[position].push(index),
+            None => {
+                keys.push(key);
+                members.push(vec![index]);
+            }
+        }
+    }
+
+    members
+        .into_iter()
+        .map(|ids| Group {
+            message: describe(&group_patch(hunks, &ids)).render(),
+            hunks: ids,
+        })
+        .collect()
+}
+
+fn show_plan(hunks: &[Hunk], groups: &[Group]) {
+    println!("\n📋 Proposed split into {} commits:", groups.len());
+    for (number, group) in groups.iter().enumerate() {
+        println!(
+            "\n{} {}",
+            format!("{})", number + 1).bright_cyan(),
+            group
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .bright_magenta()
+        );
+        for &index in &group.hunks {
+            println!("   {:>3}  {}", index + 1, hunks[index].label().dimmed());
+        }
+    }
+
+    let planned: usize = groups.iter().map(|group| group.hunks.len()).sum();
+    if planned < hunks.len() {
+        println!(
+            "{}",
+            format!(
+                "\n💡 {} hunk(s) are not in the plan and stay staged.",
+                hunks.len() - planned
+            )
+            .dimmed()
+        );
+    }
+    println!();
+}
+
+// The plan as text: "> message" starts a commit, "<n> ..." lines are its
+// hunks. Moving or deleting hunk lines changes the split.
+fn edit_plan(hunks: &[Hunk], groups: &[Group]) -> Option<Vec<Group>> {
+    let mut content = format!(
+        "{c} Each commit starts with a \"> message\" line followed by its hunks.\n{c} Move hunk lines between commits or delete them to leave them staged.\n{c} Remove every commit to abort.\n\n",
+        c = comment_char("")
+    );
+    for group in groups {
+        content.push_str(&format!(
+            "> {}\n",
+            group.message.lines().next().unwrap_or_default()
+        ));
+        for &index in &group.hunks {
+            content.push_str(&format!("{}  {}\n", index + 1, hunks[index].label()));
+        }
+        content.push('\n');
+    }
+
+    let path = Command::new("git")
+        .args(["rev-parse", "--git-path", "GIT_AI_SPLIT_PLAN"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    let path = Path::new(&path);
+    if fs::write(path, content).is_err() || !open_in_editor(path) {
+        println!("{}", "❌ Could not edit the plan. Commit aborted.".red());
+        return None;
+    }
+    let edited = fs::read_to_string(path).unwrap_or_default();
+    let _ = fs::remove_file(path);
+
+    // A hunk listed twice stays with the first commit that lists it
+    let mut assigned = vec![false; hunks.len()];
+    let mut edited_groups: Vec<Group> = Vec::new();
+    for line in strip_comments(&edited).lines() {
+        let line = line.trim();
+        if let Some(message) = line.strip_prefix('>') {
+            edited_groups.push(Group {
+                message: message.trim().to_string(),
+                hunks: Vec::new(),
+            });
+        } else if let Some(group) = edited_groups.last_mut() {
+            let id = line
+                .split_whitespace()
+                .next()
+                .and_then(|id| id.parse::<usize>().ok());
+            if let Some(index) = id.and_then(|id| id.checked_sub(1)) {
+                if index < hunks.len() && !std::mem::replace(&mut assigned[index], true) {
+                    group.hunks.push(index);
+                }
+            }
+        }
+    }
+
+    // Keep the original body when only the subject line was touched
+    for group in &mut edited_groups {
+        if let Some(original) = groups
+            .iter()
+            .find(|original| original.message.lines().next() == Some(group.message.as_str()))
+        {
+            group.message = original.message.clone();
+        }
+    }
+    edited_groups.retain(|group| !group.hunks.is_empty() && !group.message.is_empty());
+
+    if edited_groups.is_empty() {
+        println!("{}", "❌ Empty plan. Nothing was committed.".yellow());
+        return None;
+    }
+    Some(edited_groups)
+}
+
+// File headers plus the chosen hunks, in diff order
+fn group_patch(hunks: &[Hunk], ids: &[usize]) -> String {
+    staged_patch(hunks, ids, &mut HashSet::new())
+}
+
+// Like group_patch, for files in `staged` an earlier patch already applied
+// the rename or mode change, so their hunks get a plain header on the new
+// path. Records the files this patch touches.
+fn staged_patch(hunks: &[Hunk], ids: &[usize], staged: &mut HashSet<String>) -> String {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+
+    let mut patch = String::new();
+    let mut last_file: Option<&str> = None;
+    let mut touched = Vec::new();
+    for index in ids {
+        let hunk = &hunks[index];
+        if last_file != Some(hunk.file.as_str()) {
+            if staged.contains(&hunk.file) {
+                patch.push_str(&format!(
+                    "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n",
+                    hunk.file
+                ));
+            } else {
+                patch.push_str(&hunk.header);
+            }
+            last_file = Some(&hunk.file);
+            touched.push(hunk.file.clone());
+        }
+        patch.push_str(&hunk.body);
+    }
+    staged.extend(touched);
+    patch
+}
+
+// Start from HEAD's tree and apply one group at a time. If anything fails,
+// put HEAD and the index back the way they were.
+fn commit_groups(diff: &str, hunks: &[Hunk], groups: &[Group]) -> bool {
+    let original_head = git_output(&["rev-parse", "--verify", "-q", "HEAD"]);
+    reset_index(original_head.as_deref());
+    let mut staged = HashSet::new();
+
+    for (number, group) in groups.iter().enumerate() {
+        println!(
+            "\n📦 Commit {}/{}: {}",
+            number + 1,
+            groups.len(),
+            group.message.lines().next().unwrap_or_default()
+        );
+        if !apply_to_index(&staged_patch(hunks, &group.hunks, &mut staged))
+            || !run_git_commit(&group.message)
+        {
+            println!(
+                "{}",
+                "❌ Split failed, restoring the original staged changes.".red()
+            );
+            restore(original_head.as_deref(), diff);
+            return false;
+        }
+    }
+
+    let left: Vec<usize> = (0..hunks.len())
+        .filter(|index| !groups.iter().any(|group| group.hunks.contains(index)))
+        .collect();
+    if !left.is_empty() && !apply_to_index(&staged_patch(hunks, &left, &mut staged)) {
+        println!(
+            "{}",
+            "⚠️ Could not restage the hunks left out of the plan, they are still in the working tree."
+                .yellow()
+        );
+    }
+
+    println!(
+        "{}",
+        format!("✅ Created {} commits.", groups.len()).green()
+    );
+    true
+}
+
+fn restore(original_head: Option<&str>, diff: &str) {
+    match original_head {
+        Some(head) => {
+            Command::new("git")
+                .args(["reset", "-q", "--soft", head])
+                .status()
+                .expect("Failed to reset HEAD");
+        }
+        None => {
+            Command::new("git")
+                .args(["update-ref", "-d", "HEAD"])
+                .status()
+                .expect("Failed to reset HEAD");
+        }
+    }
+    reset_index(original_head);
+    apply_to_index(diff);
+}
+
+// Index back to the tree of `head`, or empty on an unborn branch
+fn reset_index(head: Option<&str>) {
+    let mut command = Command::new("git");
+    command.arg("read-tree");
+    match head {
+        Some(head) => command.arg(head),
+        None => command.arg("--empty"),
+    };
+    command.status().expect("Failed to reset the index");
+}
+
+fn git_output(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -1,4 +1,5 @@
 use crate::ai::suggest_commit_message;
+use crate::commands::commit::run_git_commit;
 use crate::commands::hunk::interactive_stage_file;
 use crate::push::push_changes;
 use crate::utils::{detect_language, get_combined_ignores, should_ignore_file, Language};
@@ -155,14 +156,7 @@ pub fn run_staging(interactive: bool, _ai: bool) {
 
             match accept_msg.trim().to_lowercase().as_str() {
                 "y" => {
-                    Command::new("git")
-                        .arg("commit")
-                        .arg("-m")
-                        .arg(suggested)
-                        .status()
-                        .expect("Failed to git commit");
-                    println!("{}", "✅ Committed with suggested message!".green());
-                    push = true;
+                    push = run_git_commit(&suggested);
                 }
                 "n" => {
                     println!("{}", "❌ Commit skipped. You can commit manually.".yellow());
@@ -172,14 +166,7 @@ pub fn run_staging(interactive: bool, _ai: bool) {
                     let mut custom_msg = String::new();
                     std::io::stdin().read_line(&mut custom_msg).unwrap();
 
-                    Command::new("git")
-                        .arg("commit")
-                        .arg("-m")
-                        .arg(custom_msg.trim())
-                        .status()
-                        .expect("Failed to git commit");
-                    println!("{}", "✅ Committed with your custom message!".green());
-                    push = true;
+                    push = run_git_commit(custom_msg.trim());
                 }
                 _ => {
                     println!("{}", "❌ Invalid choice. Commit skipped.".yellow());
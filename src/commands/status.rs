@@ -32,7 +32,7 @@ pub fn show_git_status() {
     let mut others = Vec::new();
 
     for line in status_text.lines() {
-        let status_x = line.chars().nth(0).unwrap_or(' ');
+        let status_x = line.chars().next().unwrap_or(' ');
         let status_y = line.chars().nth(1).unwrap_or(' ');
         let filename = &line[3..];
 
@@ -148,7 +148,7 @@ fn show_branch_info() {
         .expect("Failed to check branch sync");
 
     let result = String::from_utf8_lossy(&output.stdout);
-    let parts: Vec<&str> = result.trim().split_whitespace().collect();
+    let parts: Vec<&str> = result.split_whitespace().collect();
 
     if parts.len() == 2 {
         let behind: u32 = parts[0].parse().unwrap_or(0);
@@ -0,0 +1,54 @@
+use crate::ai::{header_limit, structured_output_enabled};
+use crate::commit_style::CommitStyle;
+use crate::config::GIT_AI_CONFIG;
+use colored::*;
+
+pub fn style_handler(command: &str) {
+    match command {
+        "show" => show_style(),
+        _ => {
+            println!("{}", "❌ Invalid style command.".red());
+        }
+    }
+}
+
+// What the commit prompt is told about this repository's conventions
+fn show_style() {
+    let Some(style) = CommitStyle::infer() else {
+        println!(
+            "{}",
+            "⚠️ Not enough commit history to learn a style from yet.".yellow()
+        );
+        return;
+    };
+
+    println!(
+        "🎨 Commit style learned from the last {} commits:",
+        style.sample_size
+    );
+    for rule in style.rules() {
+        println!("  • {}", rule);
+    }
+
+    // The JSON contract renders "type(scope): subject" and would override
+    // a style without it
+    if structured_output_enabled() {
+        println!(
+            "\n🧱 Structured output is on, headers up to {} characters.",
+            header_limit()
+        );
+    } else if GIT_AI_CONFIG.structured_commits != Some(false) {
+        println!(
+            "{}",
+            "\n🧱 Structured output is off here: the history doesn't use plain Conventional Commit headers, so messages are written as free text.".dimmed()
+        );
+    }
+
+    println!("\n{}", "📜 Examples sent with the prompt:".cyan());
+    for example in &style.examples {
+        for line in example.lines() {
+            println!("  {}", line.dimmed());
+        }
+        println!();
+    }
+}
@@ -0,0 +1,119 @@
+use crate::prompt_templates::{
+    self, is_known, template_path, user_template_dir, TemplateSource, BUILTIN_TEMPLATES,
+    REPO_TEMPLATE_DIR,
+};
+use crate::utils::open_in_editor;
+use colored::*;
+use std::fs;
+use std::path::PathBuf;
+
+// Without a name, list every template and where its effective text comes from
+pub fn show_template(name: Option<String>) {
+    let Some(name) = name else {
+        println!("{}", "📝 Prompt templates:".cyan());
+        for (name, _, _, purpose) in BUILTIN_TEMPLATES {
+            let template = prompt_templates::load(name);
+            println!(
+                "  {:<24} {:<10} {}",
+                name.bright_cyan(),
+                source_label(&template.source),
+                purpose.dimmed()
+            );
+        }
+        println!(
+            "{}",
+            "\n💡 Placeholders: {diff}, {summaries}, {files}, {branch}, {history}, {error}, {output}"
+                .dimmed()
+        );
+        return;
+    };
+
+    if !is_known(&name) {
+        println!(
+            "{}",
+            format!("❌ Unknown prompt template '{}'.", name).red()
+        );
+        return;
+    }
+
+    let template = prompt_templates::load(&name);
+    println!(
+        "{}",
+        format!(
+            "📝 {} ({})",
+            template.name,
+            source_description(&template.source)
+        )
+        .cyan()
+    );
+    println!("{}\n{}", "[system]".bold(), template.system);
+    println!("\n{}\n{}", "[user]".bold(), template.user);
+}
+
+// Copy the effective template into the repository (or user) directory and
+// open it, so an override always starts from what is used today
+pub fn edit_template(name: &str, user: bool) {
+    if !is_known(name) {
+        println!(
+            "{}",
+            format!("❌ Unknown prompt template '{}'.", name).red()
+        );
+        return;
+    }
+
+    let dir = if user {
+        match user_template_dir() {
+            Some(dir) => dir,
+            None => {
+                println!("{}", "❌ Could not find your home directory.".red());
+                return;
+            }
+        }
+    } else {
+        PathBuf::from(REPO_TEMPLATE_DIR)
+    };
+    let path = template_path(dir, name);
+
+    if !path.exists() {
+        let template = prompt_templates::load(name);
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, template.to_file()));
+        if let Err(e) = written {
+            println!(
+                "{}",
+                format!("❌ Failed to write {}: {}", path.display(), e).red()
+            );
+            return;
+        }
+    }
+
+    if open_in_editor(&path) {
+        println!("{}", format!("✅ Saved {}", path.display()).green());
+    } else {
+        println!(
+            "{}",
+            format!(
+                "⚠️ Could not start an editor. Edit {} by hand.",
+                path.display()
+            )
+            .yellow()
+        );
+    }
+}
+
+fn source_label(source: &TemplateSource) -> &'static str {
+    match source {
+        TemplateSource::Repo(_) => "repo",
+        TemplateSource::User(_) => "user",
+        TemplateSource::BuiltIn => "built-in",
+    }
+}
+
+fn source_description(source: &TemplateSource) -> String {
+    match source {
+        TemplateSource::Repo(path) | TemplateSource::User(path) => path.display().to_string(),
+        TemplateSource::BuiltIn => "built-in".to_string(),
+    }
+}
@@ -0,0 +1,98 @@
+use crate::config::GIT_AI_CONFIG;
+use crate::llm_usage::{self, UsageEntry};
+use colored::*;
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+struct Totals {
+    calls: usize,
+    tokens: u64,
+    cost: f64,
+}
+
+// Summaries of the usage ledger: this month by default, everything with --all
+pub fn show_usage(all: bool) {
+    let month = llm_usage::month(llm_usage::now());
+    let entries: Vec<UsageEntry> = llm_usage::load_entries()
+        .into_iter()
+        .filter(|entry| all || llm_usage::month(entry.timestamp) == month)
+        .collect();
+
+    let period = if all { "all time".to_string() } else { month };
+    if entries.is_empty() {
+        println!(
+            "{}",
+            format!("📊 No AI usage recorded for {}.", period).cyan()
+        );
+        return;
+    }
+
+    let total = totals(&entries, |_| String::new())
+        .remove("")
+        .unwrap_or_default();
+    println!(
+        "{}",
+        format!(
+            "📊 AI usage for {}: {} calls, {} tokens, ${:.4}",
+            period, total.calls, total.tokens, total.cost
+        )
+        .cyan()
+    );
+
+    if let Some(budget) = GIT_AI_CONFIG.monthly_budget_usd {
+        let spent = llm_usage::spent_this_month();
+        let line = format!("💰 Monthly budget: ${:.2} of ${:.2} used", spent, budget);
+        if spent >= budget {
+            println!("{}", format!("{}, AI calls are blocked", line).red());
+        } else {
+            println!("{}", line.green());
+        }
+    }
+
+    print_table(
+        "By day",
+        totals(&entries, |entry| llm_usage::day(entry.timestamp)),
+    );
+    print_table(
+        "By command",
+        totals(&entries, |entry| entry.command.clone()),
+    );
+    print_table(
+        "By model",
+        totals(&entries, |entry| {
+            format!("{}/{}", entry.backend, entry.model)
+        }),
+    );
+
+    if entries.iter().any(|entry| entry.estimated) {
+        println!(
+            "{}",
+            "\nℹ️ Some backends sent no token counts; those calls are estimated.".dimmed()
+        );
+    }
+}
+
+fn totals(entries: &[UsageEntry], key: impl Fn(&UsageEntry) -> String) -> BTreeMap<String, Totals> {
+    let mut groups: BTreeMap<String, Totals> = BTreeMap::new();
+    for entry in entries {
+        let group = groups.entry(key(entry)).or_default();
+        group.calls += 1;
+        group.tokens += entry.input_tokens + entry.output_tokens;
+        group.cost += entry.cost_usd;
+    }
+    groups
+}
+
+fn print_table(title: &str, groups: BTreeMap<String, Totals>) {
+    println!("\n{}", title.bold());
+    for (name, group) in groups {
+        let name = if name.is_empty() { "-" } else { name.as_str() };
+        println!(
+            "  {:<32} {:>6} calls {:>10} tokens {:>10}",
+            name,
+            group.calls,
+            group.tokens,
+            format!("${:.4}", group.cost)
+        );
+    }
+}
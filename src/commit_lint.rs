@@ -0,0 +1,179 @@
+use crate::commit_message::COMMIT_TYPES;
+use crate::config::{LintConfig, GIT_AI_CONFIG};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::process::Command;
+
+const DEFAULT_MAX_SUBJECT_LEN: usize = 72;
+const DEFAULT_BODY_WRAP: usize = 72;
+pub const DEFAULT_TICKET_PATTERN: &str = r"\b[A-Z][A-Z0-9]+-\d+\b";
+
+// "type(scope)!: description", optionally after a ticket key like "[ABC-1]"
+static HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:\[?[A-Z][A-Z0-9]+-\d+\]?:?\s+)?([A-Za-z]+)(?:\(([^)]*)\))?!?: \S").unwrap()
+});
+
+// Messages git writes itself are left alone
+const GENERATED_PREFIXES: [&str; 4] = ["Merge ", "Revert \"", "fixup! ", "squash! "];
+
+// Problems with a commit message under the `[lint]` rules in .git-ai
+pub fn lint(message: &str) -> Vec<String> {
+    let config = GIT_AI_CONFIG.lint.clone().unwrap_or_default();
+    let message = strip_comments(message);
+    let lines: Vec<&str> = message.lines().collect();
+
+    let Some(subject) = lines.first().filter(|subject| !subject.trim().is_empty()) else {
+        return vec!["message is empty".to_string()];
+    };
+    if GENERATED_PREFIXES
+        .iter()
+        .any(|prefix| subject.starts_with(prefix))
+    {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+
+    let max_subject_len = config.max_subject_len.unwrap_or(DEFAULT_MAX_SUBJECT_LEN);
+    if subject.chars().count() > max_subject_len {
+        problems.push(format!(
+            "subject is {} characters, the limit is {}",
+            subject.chars().count(),
+            max_subject_len
+        ));
+    }
+
+    if config.conventional == Some(true) {
+        problems.extend(check_conventional(subject, &config));
+    }
+
+    if lines.get(1).is_some_and(|line| !line.trim().is_empty()) {
+        problems.push("add a blank line between the subject and the body".to_string());
+    }
+
+    // Long URLs and other unbreakable tokens can't be wrapped
+    let body_wrap = config.body_wrap.unwrap_or(DEFAULT_BODY_WRAP);
+    for (number, line) in lines.iter().enumerate().skip(2) {
+        if line.chars().count() > body_wrap && line.trim().contains(' ') && !line.contains("://") {
+            problems.push(format!(
+                "body line {} is longer than {} characters",
+                number + 1,
+                body_wrap
+            ));
+        }
+    }
+
+    for word in config.forbidden_words.iter().flatten() {
+        let pattern = format!(r"(?i)\b{}\b", regex::escape(word));
+        if Regex::new(&pattern).is_ok_and(|regex| regex.is_match(&message)) {
+            problems.push(format!("contains the forbidden word \"{}\"", word));
+        }
+    }
+
+    if config.require_ticket == Some(true) {
+        let pattern = config
+            .ticket_pattern
+            .as_deref()
+            .unwrap_or(DEFAULT_TICKET_PATTERN);
+        match Regex::new(pattern) {
+            Ok(regex) if !regex.is_match(&message) => {
+                problems.push(format!("no ticket reference matching {} found", pattern))
+            }
+            Ok(_) => {}
+            Err(e) => problems.push(format!("invalid ticket_pattern '{}': {}", pattern, e)),
+        }
+    }
+
+    problems
+}
+
+fn check_conventional(subject: &str, config: &LintConfig) -> Vec<String> {
+    let Some(caps) = HEADER.captures(subject) else {
+        return vec!["subject should look like \"type(scope): description\"".to_string()];
+    };
+
+    let allowed: Vec<String> = match &config.types {
+        Some(types) => types.clone(),
+        None => COMMIT_TYPES.iter().map(|kind| kind.to_string()).collect(),
+    };
+    let mut problems = Vec::new();
+    if !allowed.contains(&caps[1].to_string()) {
+        problems.push(format!(
+            "type \"{}\" is not one of {}",
+            &caps[1],
+            allowed.join(", ")
+        ));
+    }
+    if caps.get(2).is_some_and(|scope| scope.as_str().is_empty()) {
+        problems.push("scope is empty, drop the parentheses".to_string());
+    }
+    problems
+}
+
+// core.commentString (git 2.45+) or core.commentChar, None when unset
+static COMMENT_SETTING: Lazy<Option<String>> = Lazy::new(|| {
+    ["core.commentString", "core.commentChar"]
+        .iter()
+        .find_map(|key| {
+            Command::new("git")
+                .args(["config", "--get", key])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|value| !value.is_empty())
+        })
+});
+
+// With core.commentChar=auto git picks the first of these that doesn't
+// start a line of the message
+const AUTO_COMMENT_CHARS: [&str; 10] = ["#", ";", "@", "!", "$", "%", "^", "&", "|", ":"];
+
+// Comment prefix for a template git-ai writes around `message`
+pub fn comment_char(message: &str) -> String {
+    match COMMENT_SETTING.as_deref() {
+        Some("auto") => AUTO_COMMENT_CHARS
+            .iter()
+            .find(|prefix| !message.lines().any(|line| line.starts_with(*prefix)))
+            .unwrap_or(&"#")
+            .to_string(),
+        Some(prefix) => prefix.to_string(),
+        None => "#".to_string(),
+    }
+}
+
+// For "auto", the prefix of the comment block git (or git-ai) put at the
+// end of the file, or of the scissors line of `commit -v`
+fn comment_char_in(message: &str) -> String {
+    if COMMENT_SETTING.as_deref() != Some("auto") {
+        return comment_char("");
+    }
+    let scissors = message
+        .lines()
+        .find(|line| line.ends_with(" ------------------------ >8 ------------------------"));
+    let last = message.lines().rev().find(|line| !line.trim().is_empty());
+    scissors
+        .or(last)
+        .and_then(|line| {
+            AUTO_COMMENT_CHARS
+                .iter()
+                .find(|prefix| line.starts_with(*prefix))
+        })
+        .unwrap_or(&"#")
+        .to_string()
+}
+
+// The message file of a commit-msg hook can still hold git's comments and
+// everything below the scissors line of `commit -v`
+pub fn strip_comments(message: &str) -> String {
+    let prefix = comment_char_in(message);
+    let scissors = format!("{} ------------------------ >8", prefix);
+    message
+        .lines()
+        .take_while(|line| !line.starts_with(&scissors))
+        .filter(|line| !line.starts_with(&prefix))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
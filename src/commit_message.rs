@@ -0,0 +1,250 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Value};
+
+pub const COMMIT_TYPES: [&str; 11] = [
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+pub const MAX_HEADER_LEN: usize = 72;
+pub const BODY_WRAP: usize = 72;
+
+// Appended to the commit prompts so the model answers with one JSON object
+pub const STRUCTURED_OUTPUT_INSTRUCTIONS: &str = r#"Respond with a single JSON object and nothing else, no markdown fences:
+{"type": "feat|fix|docs|style|refactor|perf|test|build|ci|chore|revert", "scope": "optional short scope or null", "subject": "imperative summary, no trailing period", "body": "optional explanation of what and why, or null", "breaking": "description of the breaking change, or null", "footers": ["optional trailers like Refs: #123"]}"#;
+
+// Follows STRUCTURED_OUTPUT_INSTRUCTIONS with the header limit in use
+pub fn header_limit_instruction(max_header_len: usize) -> String {
+    format!(
+        "The line \"type(scope): subject\" must stay under {} characters.",
+        max_header_len
+    )
+}
+
+// Label and extra instruction for each `--candidates` alternative
+pub const CANDIDATE_STYLES: [(&str, &str); 3] = [
+    ("terse", "Keep it terse: a single subject line and no body."),
+    (
+        "detailed",
+        "Be detailed: add a body explaining what changed and why.",
+    ),
+    (
+        "conventional",
+        "Follow Conventional Commits strictly and use a scope when one fits.",
+    ),
+];
+
+// "type(scope)!: subject" as a plain first line
+static PLAIN_HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([a-z]+)(?:\(([^)\s]+)\))?(!)?: (.+)$").unwrap());
+
+pub const REPAIR_PROMPT: &str = "You fix invalid commit message JSON. You are given a validation error and the previous output. Return only the corrected JSON object with the fields type, scope, subject, body, breaking and footers.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitMessage {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub subject: String,
+    pub body: Option<String>,
+    pub breaking: Option<String>,
+    pub footers: Vec<String>,
+}
+
+impl CommitMessage {
+    // Parse and validate model output, tolerating fences and chatty preambles
+    pub fn parse(raw: &str, max_header_len: usize) -> Result<Self, String> {
+        let json = extract_json_object(raw).ok_or("no JSON object found in the output")?;
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| format!("invalid JSON: {}", e))?;
+
+        let kind = text_field(&value, "type")
+            .ok_or("missing \"type\"")?
+            .to_lowercase();
+        let subject = text_field(&value, "subject")
+            .ok_or("missing \"subject\"")?
+            .trim_end_matches('.')
+            .to_string();
+        let breaking = match &value["breaking"] {
+            Value::Bool(true) => Some(String::new()),
+            _ => text_field(&value, "breaking"),
+        };
+        let footers = match &value["footers"] {
+            Value::Array(items) => items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect(),
+            Value::String(item) if !item.trim().is_empty() => vec![item.trim().to_string()],
+            _ => Vec::new(),
+        };
+
+        let message = CommitMessage {
+            kind,
+            scope: text_field(&value, "scope"),
+            subject,
+            body: text_field(&value, "body"),
+            breaking,
+            footers,
+        };
+        message.validate(max_header_len)?;
+        Ok(message)
+    }
+
+    // Read a plain "type(scope): subject" message back into its parts; other
+    // first lines become a chore subject
+    pub fn from_plain(text: &str) -> Self {
+        let text = text.trim();
+        let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+        let body = Some(rest.trim().to_string()).filter(|body| !body.is_empty());
+
+        match PLAIN_HEADER.captures(first.trim()) {
+            Some(caps) if COMMIT_TYPES.contains(&&caps[1]) => CommitMessage {
+                kind: caps[1].to_string(),
+                scope: caps.get(2).map(|scope| scope.as_str().to_string()),
+                subject: caps[4].trim_end_matches('.').to_string(),
+                body,
+                breaking: caps.get(3).map(|_| String::new()),
+                footers: Vec::new(),
+            },
+            _ => CommitMessage {
+                kind: "chore".to_string(),
+                scope: None,
+                subject: first.trim().trim_end_matches('.').to_string(),
+                body,
+                breaking: None,
+                footers: Vec::new(),
+            },
+        }
+    }
+
+    // The JSON shape STRUCTURED_OUTPUT_INSTRUCTIONS asks for
+    pub fn to_json(&self) -> String {
+        json!({
+            "type": self.kind,
+            "scope": self.scope,
+            "subject": self.subject,
+            "body": self.body,
+            "breaking": self.breaking,
+            "footers": self.footers,
+        })
+        .to_string()
+    }
+
+    fn validate(&self, max_header_len: usize) -> Result<(), String> {
+        if !COMMIT_TYPES.contains(&self.kind.as_str()) {
+            return Err(format!(
+                "type \"{}\" is not one of {}",
+                self.kind,
+                COMMIT_TYPES.join(", ")
+            ));
+        }
+        if let Some(scope) = &self.scope {
+            if scope.contains(char::is_whitespace) {
+                return Err(format!("scope \"{}\" must not contain spaces", scope));
+            }
+        }
+        if self.subject.contains('\n') {
+            return Err("subject must be a single line".to_string());
+        }
+        let header = self.header();
+        if header.chars().count() > max_header_len {
+            return Err(format!(
+                "header \"{}\" is longer than {} characters",
+                header, max_header_len
+            ));
+        }
+        Ok(())
+    }
+
+    // "type(scope)!: subject"
+    pub fn header(&self) -> String {
+        let scope = self
+            .scope
+            .as_ref()
+            .map(|scope| format!("({})", scope))
+            .unwrap_or_default();
+        let bang = if self.breaking.is_some() { "!" } else { "" };
+        format!("{}{}{}: {}", self.kind, scope, bang, self.subject)
+    }
+
+    pub fn render(&self) -> String {
+        let mut message = self.header();
+
+        if let Some(body) = &self.body {
+            message.push_str("\n\n");
+            message.push_str(&wrap(body, BODY_WRAP));
+        }
+
+        let mut trailers = Vec::new();
+        if let Some(note) = self.breaking.as_ref().filter(|note| !note.is_empty()) {
+            trailers.push(wrap(&format!("BREAKING CHANGE: {}", note), BODY_WRAP));
+        }
+        trailers.extend(self.footers.iter().cloned());
+        if !trailers.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(&trailers.join("\n"));
+        }
+
+        message
+    }
+}
+
+// Non-empty string field, with "null"/"none" answers treated as absent
+fn text_field(value: &Value, field: &str) -> Option<String> {
+    value[field]
+        .as_str()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty() && !matches!(*text, "null" | "none" | "None"))
+        .map(|text| text.to_string())
+}
+
+pub fn extract_json_object(raw: &str) -> Option<&str> {
+    let start = raw.find('{')?;
+    let end = raw.rfind('}')?;
+    (start < end).then(|| &raw[start..=end])
+}
+
+// Strip markdown fences and "Here is your commit message:" style preambles
+pub fn clean_plain_message(raw: &str) -> String {
+    let lines: Vec<&str> = raw
+        .trim()
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+
+    let skip = match lines.first() {
+        Some(first) if first.trim_end().ends_with(':') && lines.len() > 1 => 1,
+        _ => 0,
+    };
+
+    lines[skip..].join("\n").trim().to_string()
+}
+
+// Greedy word wrap that keeps existing paragraphs and list items
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut wrapped = Vec::new();
+
+    for line in text.lines() {
+        if line.chars().count() <= width {
+            wrapped.push(line.trim_end().to_string());
+            continue;
+        }
+
+        let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+        let mut current = indent.clone();
+        for word in line.split_whitespace() {
+            if current.len() > indent.len() {
+                if current.chars().count() + 1 + word.chars().count() > width {
+                    wrapped.push(std::mem::replace(&mut current, indent.clone()));
+                } else {
+                    current.push(' ');
+                }
+            }
+            current.push_str(word);
+        }
+        wrapped.push(current);
+    }
+
+    wrapped.join("\n")
+}
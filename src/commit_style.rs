@@ -0,0 +1,250 @@
+use crate::commit_message::COMMIT_TYPES;
+use crate::redact::redact;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::process::Command;
+
+const SAMPLE_SIZE: usize = 50;
+const MIN_SAMPLES: usize = 5;
+const EXAMPLE_COUNT: usize = 5;
+const EXAMPLE_MAX_LINES: usize = 6;
+// Share of commits a convention needs before we call it the house style
+const MAJORITY: f64 = 0.6;
+
+static CONVENTIONAL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([a-z]+)(?:\(([^)]+)\))?!?: (.+)$").unwrap());
+// "ABC-123: ..." or "[abc-123] ..."
+static TICKET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\[[A-Za-z][A-Za-z0-9]*-\d+\]:?|[A-Z][A-Z0-9]+-\d+:?)\s+(.+)$").unwrap()
+});
+static TRAILER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([A-Za-z][A-Za-z-]+): \S").unwrap());
+
+// Conventions inferred from recent commits, fed to the commit prompt so
+// generated messages look like the rest of the history
+pub struct CommitStyle {
+    pub sample_size: usize,
+    pub conventional: bool,
+    pub types: Vec<String>,
+    pub scopes: Vec<String>,
+    // Literal example like "[ABC-123]" when most subjects start with a ticket
+    pub ticket_prefix: Option<String>,
+    pub capitalized: bool,
+    pub imperative: bool,
+    pub trailing_period: bool,
+    pub average_subject_len: usize,
+    pub max_subject_len: usize,
+    pub uses_body: bool,
+    pub trailers: Vec<String>,
+    pub examples: Vec<String>,
+}
+
+impl CommitStyle {
+    // None in young repositories, a handful of commits is no style
+    pub fn infer() -> Option<CommitStyle> {
+        let messages = recent_messages();
+        if messages.len() < MIN_SAMPLES {
+            return None;
+        }
+        let count = messages.len() as f64;
+        let share = |matches: usize| matches as f64 / count >= MAJORITY;
+
+        let mut tickets = Vec::new();
+        let mut conventional = 0;
+        let mut types: HashMap<String, usize> = HashMap::new();
+        let mut scopes: HashMap<String, usize> = HashMap::new();
+        let (mut capitalized, mut imperative, mut periods, mut bodies) = (0, 0, 0, 0);
+        let mut trailers: HashMap<String, usize> = HashMap::new();
+        let mut lengths = Vec::new();
+
+        for message in &messages {
+            let subject = message.lines().next().unwrap_or_default();
+            lengths.push(subject.chars().count());
+
+            let mut text = subject;
+            if let Some(caps) = TICKET.captures(text) {
+                tickets.push(caps.get(1).map_or("", |m| m.as_str()).to_string());
+                text = caps.get(2).map_or(text, |m| m.as_str());
+            }
+            if let Some(caps) = CONVENTIONAL.captures(text) {
+                if COMMIT_TYPES.contains(&&caps[1]) {
+                    conventional += 1;
+                    *types.entry(caps[1].to_string()).or_insert(0) += 1;
+                    if let Some(scope) = caps.get(2) {
+                        *scopes.entry(scope.as_str().to_string()).or_insert(0) += 1;
+                    }
+                    text = caps.get(3).map_or(text, |m| m.as_str());
+                }
+            }
+
+            if text.starts_with(|c: char| c.is_uppercase()) {
+                capitalized += 1;
+            }
+            if looks_imperative(text) {
+                imperative += 1;
+            }
+            if text.ends_with('.') {
+                periods += 1;
+            }
+
+            let paragraphs: Vec<&str> = message.split("\n\n").skip(1).collect();
+            let trailer_keys = paragraphs
+                .last()
+                .map(|last| trailer_keys(last))
+                .unwrap_or_default();
+            if paragraphs.len() > usize::from(!trailer_keys.is_empty()) {
+                bodies += 1;
+            }
+            for key in trailer_keys {
+                *trailers.entry(key).or_insert(0) += 1;
+            }
+        }
+        // A trailer on a third of the commits is a habit worth keeping. People
+        // trailers can't be guessed from a diff, so leave those out.
+        trailers.retain(|key, uses| {
+            *uses * 3 >= messages.len() && key != "Co-authored-by" && key != "Signed-off-by"
+        });
+
+        Some(CommitStyle {
+            sample_size: messages.len(),
+            conventional: share(conventional),
+            types: most_common(types, 6),
+            scopes: most_common(scopes, 8),
+            ticket_prefix: share(tickets.len())
+                .then(|| tickets.first().cloned())
+                .flatten(),
+            capitalized: share(capitalized),
+            imperative: share(imperative),
+            trailing_period: share(periods),
+            average_subject_len: lengths.iter().sum::<usize>() / lengths.len(),
+            max_subject_len: lengths.iter().copied().max().unwrap_or(0),
+            uses_body: share(bodies),
+            trailers: most_common(trailers, 4),
+            examples: messages
+                .iter()
+                .take(EXAMPLE_COUNT)
+                .map(|message| {
+                    message
+                        .lines()
+                        .take(EXAMPLE_MAX_LINES)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .collect(),
+        })
+    }
+
+    // One line per convention, in the words the prompt uses
+    pub fn rules(&self) -> Vec<String> {
+        let mut rules = Vec::new();
+
+        if let Some(prefix) = &self.ticket_prefix {
+            rules.push(format!(
+                "Start the subject with the ticket key in the same form as \"{}\"",
+                prefix
+            ));
+        }
+        if self.conventional {
+            let mut rule = format!(
+                "Use Conventional Commits, mostly types: {}",
+                self.types.join(", ")
+            );
+            if !self.scopes.is_empty() {
+                rule.push_str(&format!("; scopes in use: {}", self.scopes.join(", ")));
+            }
+            rules.push(rule);
+        } else {
+            rules.push("Do not use Conventional Commit prefixes like \"feat:\"".to_string());
+        }
+        rules.push(if self.capitalized {
+            "Start the subject text with a capital letter".to_string()
+        } else {
+            "Start the subject text in lowercase".to_string()
+        });
+        if self.imperative {
+            rules.push("Use the imperative mood (\"Add\", not \"Added\")".to_string());
+        }
+        rules.push(if self.trailing_period {
+            "End the subject with a period".to_string()
+        } else {
+            "No period at the end of the subject".to_string()
+        });
+        rules.push(format!(
+            "Subjects average {} characters, the longest is {}",
+            self.average_subject_len, self.max_subject_len
+        ));
+        rules.push(if self.uses_body {
+            "Usually add a body explaining what and why".to_string()
+        } else {
+            "Usually a subject line only, no body".to_string()
+        });
+        if !self.trailers.is_empty() {
+            rules.push(format!("Common trailers: {}", self.trailers.join(", ")));
+        }
+
+        rules
+    }
+
+    // Appended to the commit system prompt
+    pub fn prompt(&self) -> String {
+        let rules: Vec<String> = self
+            .rules()
+            .iter()
+            .map(|rule| format!("- {}", rule))
+            .collect();
+        format!(
+            "Match this repository's commit style:\n{}\n\nRecent commit messages for reference:\n{}",
+            rules.join("\n"),
+            // Old messages can hold emails and tokens like any diff
+            redact(&self.examples.join("\n---\n")).text
+        )
+    }
+}
+
+// Full messages of recent non-merge commits, newest first
+fn recent_messages() -> Vec<String> {
+    let output = Command::new("git")
+        .args([
+            "log",
+            "--no-merges",
+            &format!("-n{}", SAMPLE_SIZE),
+            "--format=%B%x00",
+        ])
+        .output();
+
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty())
+        .collect()
+}
+
+// "Added"/"Adds" are the usual non-imperative forms; "Address" is fine
+fn looks_imperative(text: &str) -> bool {
+    let word = text
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    !(word.ends_with("ed") || (word.ends_with('s') && !word.ends_with("ss")))
+}
+
+fn trailer_keys(paragraph: &str) -> Vec<String> {
+    let lines: Vec<&str> = paragraph.lines().collect();
+    if lines.is_empty() || !lines.iter().all(|line| TRAILER.is_match(line)) {
+        return Vec::new();
+    }
+    lines
+        .iter()
+        .filter_map(|line| TRAILER.captures(line).map(|caps| caps[1].to_string()))
+        .collect()
+}
+
+fn most_common(counts: HashMap<String, usize>, limit: usize) -> Vec<String> {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.into_iter().take(limit).map(|(key, _)| key).collect()
+}
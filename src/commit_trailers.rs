@@ -0,0 +1,225 @@
+use crate::commands::commit::message_trailers;
+use crate::commit_lint::DEFAULT_TICKET_PATTERN;
+use crate::config::{TicketRule, TrailersConfig, GIT_AI_CONFIG};
+use colored::*;
+use regex::Regex;
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const DEFAULT_TICKET_TRAILER: &str = "Refs";
+const DEFAULT_PREFIX_FORMAT: &str = "{ticket}: ";
+const CO_AUTHORED_BY: &str = "Co-authored-by";
+
+// The branch's ticket and the current co-authors, added to a message right
+// before git-ai commits it. Anything already in the message is left alone.
+pub fn decorate(message: &str) -> String {
+    let config = GIT_AI_CONFIG.trailers.clone().unwrap_or_default();
+    let mut message = message.to_string();
+    let mut trailers = Vec::new();
+
+    if let Some(ticket) = branch_ticket(&config).filter(|ticket| !message.contains(ticket.as_str()))
+    {
+        match config.ticket.as_deref() {
+            Some("prefix") => {
+                let format = config
+                    .prefix_format
+                    .as_deref()
+                    .unwrap_or(DEFAULT_PREFIX_FORMAT);
+                message = format!("{}{}", format.replace("{ticket}", &ticket), message);
+            }
+            Some("trailer") => trailers.push(format!(
+                "{}: {}",
+                config
+                    .ticket_trailer
+                    .as_deref()
+                    .unwrap_or(DEFAULT_TICKET_TRAILER),
+                ticket
+            )),
+            _ => {}
+        }
+    }
+
+    for co_author in co_authors(&config) {
+        trailers.push(format!("{}: {}", CO_AUTHORED_BY, co_author));
+    }
+
+    if trailers.is_empty() {
+        return message;
+    }
+    add_trailers(&message, &trailers)
+}
+
+// First `ticket_rules` entry matching the current branch; without rules the
+// lint ticket pattern is searched anywhere in the name
+fn branch_ticket(config: &TrailersConfig) -> Option<String> {
+    config.ticket.as_ref()?;
+    let branch = git_output(&["symbolic-ref", "--short", "-q", "HEAD"])?;
+
+    let rules = match &config.ticket_rules {
+        Some(rules) => rules.clone(),
+        None => vec![TicketRule {
+            branch: GIT_AI_CONFIG
+                .lint
+                .as_ref()
+                .and_then(|lint| lint.ticket_pattern.clone())
+                .unwrap_or_else(|| DEFAULT_TICKET_PATTERN.to_string()),
+            ticket: None,
+        }],
+    };
+
+    for rule in rules {
+        let regex = match Regex::new(&rule.branch) {
+            Ok(regex) => regex,
+            Err(e) => {
+                println!(
+                    "{}",
+                    format!("⚠️ Invalid ticket rule '{}': {}", rule.branch, e).yellow()
+                );
+                continue;
+            }
+        };
+        let Some(caps) = regex.captures(&branch) else {
+            continue;
+        };
+        let ticket = match &rule.ticket {
+            Some(template) => {
+                let mut ticket = String::new();
+                caps.expand(template, &mut ticket);
+                ticket
+            }
+            None => caps.get(1).unwrap_or(caps.get(0)?).as_str().to_string(),
+        };
+        if !ticket.is_empty() {
+            return Some(ticket);
+        }
+    }
+    None
+}
+
+// GIT_AI_PAIR="alice,bob" picks from the `[team]` roster (or takes
+// "Name <email>" as is, empty means solo); otherwise, with
+// `pair_history_hours` set, the co-authors of your own last commit carry over
+// until that many hours after the pairing started
+fn co_authors(config: &TrailersConfig) -> Vec<String> {
+    if let Ok(pair) = env::var("GIT_AI_PAIR") {
+        return pair
+            .split(',')
+            .map(|alias| alias.trim())
+            .filter(|alias| !alias.is_empty())
+            .filter_map(|alias| {
+                let member = GIT_AI_CONFIG
+                    .team
+                    .as_ref()
+                    .and_then(|team| team.get(alias))
+                    .cloned();
+                match member {
+                    Some(member) => Some(member),
+                    None if alias.contains('<') => Some(alias.to_string()),
+                    None => {
+                        println!(
+                            "{}",
+                            format!("⚠️ '{}' is not in the [team] roster, skipped.", alias)
+                                .yellow()
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+    }
+
+    let hours = config.pair_history_hours.unwrap_or(0);
+    if hours == 0 {
+        return Vec::new();
+    }
+    let Some(email) = git_output(&["config", "user.email"]) else {
+        return Vec::new();
+    };
+    let author = format!("--author=<{}>", email);
+    let window = format!("{} hours ago", hours);
+
+    // Your commits inside the window, newest first
+    let recent = git_output(&[
+        "log",
+        "--format=%B%x00",
+        &author,
+        &format!("--since={}", window),
+    ])
+    .unwrap_or_default();
+    let mut messages = recent
+        .split('\0')
+        .map(str::trim)
+        .filter(|message| !message.is_empty());
+    let Some(last) = messages.next() else {
+        return Vec::new();
+    };
+    let pair = co_authored_by(last);
+    if pair.is_empty() {
+        return pair;
+    }
+
+    // Carried-over trailers look like the pairing they came from, so only
+    // carry over when the run of commits with these co-authors started inside
+    // the window: an older commit in it, or the last one before it, differs
+    let started_in_window = messages.any(|message| co_authored_by(message) != pair)
+        || git_output(&[
+            "log",
+            "-1",
+            "--format=%B",
+            &author,
+            &format!("--until={}", window),
+        ])
+        .is_none_or(|message| co_authored_by(&message) != pair);
+    if started_in_window {
+        pair
+    } else {
+        Vec::new()
+    }
+}
+
+fn co_authored_by(message: &str) -> Vec<String> {
+    message_trailers(message)
+        .iter()
+        .filter_map(|trailer| trailer.split_once(": "))
+        .filter(|(key, _)| key.eq_ignore_ascii_case(CO_AUTHORED_BY))
+        .map(|(_, value)| value.trim().to_string())
+        .collect()
+}
+
+// `git interpret-trailers` places them after any existing trailers and skips
+// exact duplicates, so amending doesn't stack copies
+fn add_trailers(message: &str, trailers: &[String]) -> String {
+    let mut command = Command::new("git");
+    command.args(["interpret-trailers", "--if-exists", "addIfDifferent"]);
+    for trailer in trailers {
+        command.arg("--trailer").arg(trailer);
+    }
+    let Ok(mut child) = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn() else {
+        return message.to_string();
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Without the final newline the subject is read as a trailer block
+        let _ = stdin.write_all(format!("{}\n", message.trim_end()).as_bytes());
+    }
+    child
+        .wait_with_output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim_end()
+                .to_string()
+        })
+        .unwrap_or_else(|| message.to_string())
+}
+
+fn git_output(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|output| !output.is_empty())
+}
@@ -1,9 +1,9 @@
 use once_cell::sync::Lazy;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
-use toml;
 
-pub static GIT_AI_CONFIG: Lazy<GitAIConfig> = Lazy::new(|| load_git_ai_config());
+pub static GIT_AI_CONFIG: Lazy<GitAIConfig> = Lazy::new(load_git_ai_config);
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct GitAIConfig {
@@ -12,6 +12,93 @@ pub struct GitAIConfig {
     pub editor: Option<String>,
     pub llm_backend: Option<String>,
     pub ai_enabled: Option<bool>,
+    pub stream: Option<bool>,
+    pub structured_commits: Option<bool>,
+    pub max_retries: Option<u32>,
+    pub cache: Option<bool>,
+    pub cache_ttl_secs: Option<u64>,
+    pub cache_max_mb: Option<u64>,
+    pub fallback: Option<Vec<String>>,
+    pub monthly_budget_usd: Option<f64>,
+    pub redact: Option<bool>,
+    pub redact_patterns: Option<Vec<String>>,
+    pub learn_style: Option<bool>,
+    pub protected_branches: Option<Vec<String>>,
+    pub providers: Option<HashMap<String, ProviderConfig>>,
+    pub http: Option<HttpConfig>,
+    pub lint: Option<LintConfig>,
+    pub trailers: Option<TrailersConfig>,
+    // alias -> "Name <email>", for Co-authored-by trailers
+    pub team: Option<HashMap<String, String>>,
+}
+
+// `[http]` in .git-ai, shared by every provider
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HttpConfig {
+    pub proxy: Option<String>,
+    pub no_proxy: Option<String>,
+    pub ca_bundle: Option<String>,
+    pub connect_timeout_secs: Option<u64>,
+    pub read_timeout_secs: Option<u64>,
+    pub user_agent: Option<String>,
+}
+
+// `[lint]` in .git-ai, checked by `git-ai lint-msg` and on AI suggestions
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct LintConfig {
+    pub conventional: Option<bool>,
+    pub types: Option<Vec<String>>,
+    pub max_subject_len: Option<usize>,
+    pub body_wrap: Option<usize>,
+    pub forbidden_words: Option<Vec<String>>,
+    pub require_ticket: Option<bool>,
+    pub ticket_pattern: Option<String>,
+}
+
+// `[trailers]` in .git-ai, applied to every message git-ai commits
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TrailersConfig {
+    // "prefix" or "trailer"; the branch's ticket is not added when unset
+    pub ticket: Option<String>,
+    pub ticket_rules: Option<Vec<TicketRule>>,
+    pub ticket_trailer: Option<String>,
+    pub prefix_format: Option<String>,
+    // Reuse the co-authors of your own last commit for this many hours after
+    // the pairing started; off when unset
+    pub pair_history_hours: Option<u64>,
+}
+
+// Branch name regex and the ticket it yields, e.g. "$1" for the first group
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TicketRule {
+    pub branch: String,
+    pub ticket: Option<String>,
+}
+
+// Per-provider overrides, e.g. `[providers.openai]` in .git-ai
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProviderConfig {
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub timeout_secs: Option<u64>,
+    pub context_tokens: Option<usize>,
+    // USD per million tokens, for models missing from the built-in price table
+    pub input_cost_per_mtok: Option<f64>,
+    pub output_cost_per_mtok: Option<f64>,
+    // Ollama only: how long the model stays loaded after a request, e.g. "10m"
+    pub keep_alive: Option<String>,
+}
+
+impl GitAIConfig {
+    pub fn provider(&self, name: &str) -> ProviderConfig {
+        self.providers
+            .as_ref()
+            .and_then(|providers| providers.get(name))
+            .cloned()
+            .unwrap_or_default()
+    }
 }
 
 impl Default for GitAIConfig {
@@ -22,6 +109,23 @@ impl Default for GitAIConfig {
             editor: None,
             llm_backend: None,
             ai_enabled: Some(false),
+            stream: Some(true),
+            structured_commits: Some(true),
+            max_retries: Some(3),
+            cache: Some(true),
+            cache_ttl_secs: None,
+            cache_max_mb: None,
+            fallback: None,
+            monthly_budget_usd: None,
+            redact: Some(true),
+            redact_patterns: None,
+            learn_style: None,
+            protected_branches: None,
+            providers: None,
+            http: None,
+            lint: None,
+            trailers: None,
+            team: None,
         }
     }
 }
@@ -0,0 +1,309 @@
+use crate::commit_message::CommitMessage;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeSet;
+
+const MAX_SUBJECT_LEN: usize = 50;
+
+// Definitions in the languages we are most likely to see: Rust, Python,
+// JS/TS, Go, plus classes and types in general
+static DEFINITION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?:pub(?:\([a-z]+\))?\s+)?(?:export\s+)?(?:default\s+)?(?:async\s+)?(?:fn|def|function|func|class|struct|enum|trait|interface)\s+(?:\([^)]*\)\s*)?([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .unwrap()
+});
+
+const MANIFESTS: [&str; 14] = [
+    "Cargo.toml",
+    "Cargo.lock",
+    "package.json",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.mod",
+    "go.sum",
+    "requirements.txt",
+    "pyproject.toml",
+    "poetry.lock",
+    "Gemfile",
+    "Gemfile.lock",
+    "pom.xml",
+];
+
+#[derive(PartialEq)]
+enum Change {
+    Added,
+    Deleted,
+    Modified,
+    Renamed { from: String },
+}
+
+struct FileChange {
+    path: String,
+    change: Change,
+    // Lines added / removed in the hunks, without the +/- marker
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+// What a staged diff does, read without any model: file kinds, status,
+// renames and functions that appeared or disappeared
+struct DiffAnalysis {
+    files: Vec<FileChange>,
+    added_definitions: Vec<String>,
+    removed_definitions: Vec<String>,
+}
+
+impl DiffAnalysis {
+    fn new(diff: &str) -> Self {
+        let files = parse_files(diff);
+
+        let definitions = |lines: fn(&FileChange) -> &Vec<String>| -> BTreeSet<String> {
+            files
+                .iter()
+                .filter(|file| is_source(&file.path))
+                .flat_map(|file| lines(file).iter())
+                .filter_map(|line| DEFINITION.captures(line).map(|caps| caps[1].to_string()))
+                .collect()
+        };
+        let added = definitions(|file| &file.added);
+        let removed = definitions(|file| &file.removed);
+
+        // A definition on both sides only had its signature touched
+        DiffAnalysis {
+            added_definitions: added.difference(&removed).cloned().collect(),
+            removed_definitions: removed.difference(&added).cloned().collect(),
+            files,
+        }
+    }
+
+    fn all(&self, check: impl Fn(&FileChange) -> bool) -> bool {
+        !self.files.is_empty() && self.files.iter().all(check)
+    }
+
+    fn kind(&self) -> &'static str {
+        if self.all(|file| is_manifest(&file.path)) {
+            "build"
+        } else if self.all(|file| is_docs(&file.path)) {
+            "docs"
+        } else if self.all(|file| is_test(&file.path)) {
+            "test"
+        } else if self.all(|file| is_ci(&file.path)) {
+            "ci"
+        } else if self.is_pure_rename() {
+            "refactor"
+        } else if !self.added_definitions.is_empty()
+            || self.all(|file| file.change == Change::Added)
+        {
+            "feat"
+        } else if !self.removed_definitions.is_empty() {
+            "refactor"
+        } else {
+            "chore"
+        }
+    }
+
+    fn is_pure_rename(&self) -> bool {
+        self.all(|file| {
+            matches!(file.change, Change::Renamed { .. })
+                && file.added.is_empty()
+                && file.removed.is_empty()
+        })
+    }
+
+    fn scope(&self) -> Option<String> {
+        if self.all(|file| is_manifest(&file.path)) {
+            return Some("deps".to_string());
+        }
+        let paths: Vec<&str> = self.files.iter().map(|file| file.path.as_str()).collect();
+        common_scope(&paths)
+    }
+
+    fn subject(&self) -> String {
+        let names: Vec<&str> = self
+            .files
+            .iter()
+            .map(|file| file_name(&file.path))
+            .collect();
+
+        let candidates = if self.all(|file| is_manifest(&file.path)) {
+            vec![
+                format!("update dependencies in {}", names.join(", ")),
+                "update dependencies".to_string(),
+            ]
+        } else if self.is_pure_rename() {
+            let renames: Vec<String> = self
+                .files
+                .iter()
+                .filter_map(|file| match &file.change {
+                    Change::Renamed { from } => {
+                        Some(format!("{} to {}", file_name(from), file_name(&file.path)))
+                    }
+                    _ => None,
+                })
+                .collect();
+            vec![
+                format!("rename {}", renames.join(", ")),
+                format!("rename {} files", renames.len()),
+            ]
+        } else if !self.added_definitions.is_empty() && !self.all(|file| is_test(&file.path)) {
+            vec![
+                format!("add {}", list(&self.added_definitions)),
+                format!("add {} new definitions", self.added_definitions.len()),
+            ]
+        } else if !self.removed_definitions.is_empty()
+            && !self.all(|file| is_test(&file.path) || file.change == Change::Deleted)
+        {
+            vec![
+                format!("remove {}", list(&self.removed_definitions)),
+                format!("remove {} definitions", self.removed_definitions.len()),
+            ]
+        } else {
+            let verb = if self.all(|file| file.change == Change::Added) {
+                "add"
+            } else if self.all(|file| file.change == Change::Deleted) {
+                "remove"
+            } else {
+                "update"
+            };
+            vec![
+                format!("{} {}", verb, list(&names)),
+                format!("{} {} files", verb, names.len()),
+            ]
+        };
+
+        // First wording that fits, long names fall back to a count
+        candidates
+            .iter()
+            .find(|subject| subject.chars().count() <= MAX_SUBJECT_LEN)
+            .or(candidates.last())
+            .cloned()
+            .unwrap_or_else(|| "update files".to_string())
+    }
+}
+
+// Conventional commit guessed from the staged diff alone, used when no
+// LLM is configured and as the offline fallback backend
+pub fn describe(diff: &str) -> CommitMessage {
+    let analysis = DiffAnalysis::new(diff);
+
+    CommitMessage {
+        kind: analysis.kind().to_string(),
+        scope: analysis.scope(),
+        subject: analysis.subject(),
+        body: None,
+        breaking: None,
+        footers: Vec::new(),
+    }
+}
+
+// Coarse bucket for grouping files into separate commits: the change kind
+// for docs/tests/CI/dependencies, otherwise the top-level directory
+pub fn file_group(path: &str) -> String {
+    let kind = if is_manifest(path) {
+        "build"
+    } else if is_docs(path) {
+        "docs"
+    } else if is_test(path) {
+        "test"
+    } else if is_ci(path) {
+        "ci"
+    } else {
+        return common_scope(&[path]).unwrap_or_default();
+    };
+    kind.to_string()
+}
+
+fn parse_files(diff: &str) -> Vec<FileChange> {
+    let mut files: Vec<FileChange> = Vec::new();
+
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git a/") {
+            // The b/ side is the new name of a renamed file
+            let path = rest.rsplit_once(" b/").map_or(rest, |(_, path)| path);
+            files.push(FileChange {
+                path: path.to_string(),
+                change: Change::Modified,
+                added: Vec::new(),
+                removed: Vec::new(),
+            });
+        } else if let Some(file) = files.last_mut() {
+            if line.starts_with("new file mode") {
+                file.change = Change::Added;
+            } else if line.starts_with("deleted file mode") {
+                file.change = Change::Deleted;
+            } else if let Some(from) = line.strip_prefix("rename from ") {
+                file.change = Change::Renamed {
+                    from: from.to_string(),
+                };
+            } else if line.starts_with("+++ ") || line.starts_with("--- ") {
+                continue;
+            } else if let Some(added) = line.strip_prefix('+') {
+                file.added.push(added.to_string());
+            } else if let Some(removed) = line.strip_prefix('-') {
+                file.removed.push(removed.to_string());
+            }
+        }
+    }
+
+    files
+}
+
+// "a, b and c", or the first two and a count
+fn list<S: AsRef<str>>(items: &[S]) -> String {
+    let items: Vec<&str> = items.iter().map(|item| item.as_ref()).collect();
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].to_string(),
+        2 | 3 => format!(
+            "{} and {}",
+            items[..items.len() - 1].join(", "),
+            items[items.len() - 1]
+        ),
+        n => format!("{} and {} more", items[..2].join(", "), n - 2),
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+// Shared top-level directory, e.g. "src" for src/a.rs and src/b/c.rs
+fn common_scope(paths: &[&str]) -> Option<String> {
+    let first = paths.first()?.split_once('/')?.0;
+    paths
+        .iter()
+        .all(|path| path.split_once('/').map(|(dir, _)| dir) == Some(first))
+        .then(|| first.trim_start_matches('.').to_string())
+        .filter(|scope| !scope.is_empty())
+}
+
+fn is_manifest(path: &str) -> bool {
+    MANIFESTS.contains(&file_name(path))
+}
+
+fn is_docs(path: &str) -> bool {
+    path.ends_with(".md") || path.starts_with("docs/") || path.ends_with(".txt")
+}
+
+fn is_test(path: &str) -> bool {
+    path.starts_with("tests/")
+        || path.contains("/tests/")
+        || path.contains("_test.")
+        || path.contains(".test.")
+        || path.contains(".spec.")
+        || file_name(path).starts_with("test_")
+}
+
+fn is_ci(path: &str) -> bool {
+    path.starts_with(".github/") || path.starts_with(".gitlab-ci") || path.starts_with(".circleci/")
+}
+
+fn is_source(path: &str) -> bool {
+    let extensions = [
+        ".rs", ".py", ".js", ".jsx", ".ts", ".tsx", ".go", ".java", ".kt", ".rb", ".swift", ".c",
+        ".cc", ".cpp", ".h", ".cs", ".php",
+    ];
+    extensions.iter().any(|ext| path.ends_with(ext))
+}
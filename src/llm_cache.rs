@@ -0,0 +1,145 @@
+use crate::config::GIT_AI_CONFIG;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Set by `--no-cache` for the current run
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+const DEFAULT_MAX_MB: u64 = 20;
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    created_at: u64,
+    backend: String,
+    model: String,
+    response: String,
+}
+
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+fn enabled() -> bool {
+    !DISABLED.load(Ordering::Relaxed) && GIT_AI_CONFIG.cache != Some(false)
+}
+
+// .git/git-ai/cache, or None outside a repository
+pub fn cache_dir() -> Option<PathBuf> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--git-dir")
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let git_dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(PathBuf::from(git_dir).join("git-ai").join("cache"))
+}
+
+// Same request, same key: hashed like a git blob so it's stable across builds
+pub fn key(backend: &str, model: &str, system_prompt: &str, input: &str) -> String {
+    let material = [backend, model, system_prompt, input].join("\0");
+    git2::Oid::hash_object(git2::ObjectType::Blob, material.as_bytes())
+        .map(|oid| oid.to_string())
+        .unwrap_or_default()
+}
+
+pub fn get(key: &str) -> Option<String> {
+    if !enabled() {
+        return None;
+    }
+
+    let path = cache_dir()?.join(format!("{}.json", key));
+    let entry: CacheEntry = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+
+    let ttl = GIT_AI_CONFIG.cache_ttl_secs.unwrap_or(DEFAULT_TTL_SECS);
+    if now().saturating_sub(entry.created_at) > ttl {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+
+    Some(entry.response)
+}
+
+// Best effort: a cache that can't be written is just a cache miss next time
+pub fn put(key: &str, backend: &str, model: &str, response: &str) {
+    if !enabled() || key.is_empty() {
+        return;
+    }
+    let Some(dir) = cache_dir() else {
+        return;
+    };
+    if fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+
+    let entry = CacheEntry {
+        created_at: now(),
+        backend: backend.to_string(),
+        model: model.to_string(),
+        response: response.to_string(),
+    };
+    if let Ok(json) = serde_json::to_string(&entry) {
+        let _ = fs::write(dir.join(format!("{}.json", key)), json);
+    }
+
+    enforce_size_cap(&dir);
+}
+
+// Drop the oldest entries until the cache fits in `cache_max_mb`
+fn enforce_size_cap(dir: &PathBuf) {
+    let max_bytes = GIT_AI_CONFIG.cache_max_mb.unwrap_or(DEFAULT_MAX_MB) * 1024 * 1024;
+
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir
+            .flatten()
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+            })
+            .collect(),
+        Err(_) => return,
+    };
+
+    let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    entries.sort_by_key(|(modified, _, _)| *modified);
+
+    for (_, len, path) in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total = total.saturating_sub(len);
+        }
+    }
+}
+
+// Returns how many entries were removed
+pub fn clear() -> usize {
+    let Some(dir) = cache_dir() else {
+        return 0;
+    };
+    let count = fs::read_dir(&dir)
+        .map(|read_dir| read_dir.flatten().count())
+        .unwrap_or(0);
+
+    if fs::remove_dir_all(&dir).is_err() {
+        return 0;
+    }
+    count
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
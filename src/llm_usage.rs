@@ -0,0 +1,215 @@
+use crate::chunking::estimate_tokens;
+use crate::config::GIT_AI_CONFIG;
+use crate::llms::error::LLMError;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// git-ai subcommand being run, e.g. "commit"
+static COMMAND: OnceCell<String> = OnceCell::new();
+
+// Providers report the token counts of their response here; every backend
+// call runs inside its own scope so parallel requests don't mix them up
+tokio::task_local! {
+    static TOKENS: Cell<Option<(u64, u64)>>;
+}
+
+// USD per million (input, output) tokens, matched by model name prefix.
+// More specific prefixes come first.
+const PRICES: [(&str, f64, f64); 16] = [
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4.1-nano", 0.1, 0.4),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("gpt-4-turbo", 10.0, 30.0),
+    ("gpt-4", 30.0, 60.0),
+    ("gpt-3.5-turbo", 0.5, 1.5),
+    ("claude-3-opus", 15.0, 75.0),
+    ("claude-opus", 15.0, 75.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("claude", 3.0, 15.0),
+    ("gemini-1.5-flash", 0.075, 0.3),
+    ("gemini-1.5-pro", 1.25, 5.0),
+    ("gemini", 0.1, 0.4),
+];
+
+// Backends that run locally or offline and never bill anything
+const FREE_BACKENDS: [&str; 5] = ["ollama", "mock", "heuristic", "replay", "nollm"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UsageEntry {
+    pub timestamp: u64,
+    pub command: String,
+    pub backend: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    // The provider sent no usage fields, so tokens were estimated from text
+    pub estimated: bool,
+    pub cost_usd: f64,
+    pub latency_ms: u64,
+}
+
+pub fn set_command(command: &str) {
+    let _ = COMMAND.set(command.to_string());
+}
+
+// Called by providers once they have parsed the usage block of a response
+pub fn report_tokens(input_tokens: u64, output_tokens: u64) {
+    let _ = TOKENS.try_with(|tokens| tokens.set(Some((input_tokens, output_tokens))));
+}
+
+// Run one backend call and capture whatever token counts it reported
+pub async fn measure<T>(call: impl Future<Output = T>) -> (T, Option<(u64, u64)>) {
+    TOKENS
+        .scope(Cell::new(None), async {
+            let output = call.await;
+            (output, TOKENS.with(|tokens| tokens.get()))
+        })
+        .await
+}
+
+// ~/.git-ai/usage.jsonl, shared by every repository so the budget is per user
+pub fn ledger_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("GIT_AI_USAGE_LEDGER") {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()?;
+    Some(PathBuf::from(home).join(".git-ai").join("usage.jsonl"))
+}
+
+pub fn record(
+    backend: &str,
+    model: &str,
+    tokens: Option<(u64, u64)>,
+    system_prompt: &str,
+    input: &str,
+    response: &str,
+    latency: Duration,
+) {
+    let (input_tokens, output_tokens) = tokens.unwrap_or_else(|| {
+        (
+            estimate_tokens(&format!("{}{}", system_prompt, input)) as u64,
+            estimate_tokens(response) as u64,
+        )
+    });
+
+    let entry = UsageEntry {
+        timestamp: now(),
+        command: COMMAND.get().cloned().unwrap_or_default(),
+        backend: backend.to_string(),
+        model: model.to_string(),
+        input_tokens,
+        output_tokens,
+        estimated: tokens.is_none(),
+        cost_usd: estimate_cost(backend, model, input_tokens, output_tokens),
+        latency_ms: latency.as_millis() as u64,
+    };
+
+    // Best effort, like the cache: a ledger that can't be written never
+    // blocks a commit
+    let Some(path) = ledger_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let (Ok(mut file), Ok(json)) = (
+        OpenOptions::new().create(true).append(true).open(&path),
+        serde_json::to_string(&entry),
+    ) {
+        let _ = writeln!(file, "{}", json);
+    }
+}
+
+// `[providers.<name>]` prices win over the built-in table
+fn estimate_cost(backend: &str, model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    let config = GIT_AI_CONFIG.provider(backend);
+    let (input_price, output_price) =
+        match (config.input_cost_per_mtok, config.output_cost_per_mtok) {
+            (None, None) if FREE_BACKENDS.contains(&backend) => (0.0, 0.0),
+            (None, None) => PRICES
+                .iter()
+                .find(|(prefix, _, _)| model.starts_with(prefix))
+                .map(|(_, input, output)| (*input, *output))
+                .unwrap_or((0.0, 0.0)),
+            (input, output) => (input.unwrap_or(0.0), output.unwrap_or(0.0)),
+        };
+
+    (input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0
+}
+
+pub fn load_entries() -> Vec<UsageEntry> {
+    ledger_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn spent_this_month() -> f64 {
+    let month = month(now());
+    load_entries()
+        .iter()
+        .filter(|entry| self::month(entry.timestamp) == month)
+        .map(|entry| entry.cost_usd)
+        .sum()
+}
+
+// Refuse new calls once `monthly_budget_usd` is used up
+pub fn check_budget() -> Result<(), LLMError> {
+    let Some(budget) = GIT_AI_CONFIG.monthly_budget_usd else {
+        return Ok(());
+    };
+
+    let spent = spent_this_month();
+    if spent >= budget {
+        return Err(LLMError::BudgetExceeded { spent, budget });
+    }
+    Ok(())
+}
+
+// "2026-10-18" in UTC
+pub fn day(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days((timestamp / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// "2026-10" in UTC
+pub fn month(timestamp: u64) -> String {
+    day(timestamp)[..7].to_string()
+}
+
+// Days since 1970-01-01 to a calendar date (Howard Hinnant's algorithm)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -1,67 +1,74 @@
-use super::LLMProvider;
+use super::error::{required_env, LLMError};
+use super::openai::{chat_body, chat_completion, chat_completion_stream};
+use super::stream::TokenSink;
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
 use async_trait::async_trait;
-use reqwest::Client;
-use serde_json::json;
+use reqwest::{Client, RequestBuilder};
 
-pub struct AzureOpenAIProvider;
-
-#[async_trait]
-impl LLMProvider for AzureOpenAIProvider {
-    async fn generate_commit_message(
-        diff: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(
-            diff,
-            "You are a Git commit message generator. Write clear, concise Git commit messages.",
-        )
-        .await
-    }
-    async fn generate_project_scaffolding(
-        prompt: &str,
-        input: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(input, prompt).await
-    }
+pub struct AzureOpenAIProvider {
+    settings: ProviderSettings,
+    api_key: String,
+    api_version: String,
+    client: Client,
 }
 
-async fn call(
-    input: &str,
-    system_prompt: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client = Client::new();
-    let endpoint = std::env::var("AZURE_OPENAI_ENDPOINT").expect("AZURE_OPENAI_ENDPOINT not set");
-    let api_key = std::env::var("AZURE_OPENAI_API_KEY").expect("AZURE_OPENAI_API_KEY not set");
-    let api_version =
-        std::env::var("AZURE_OPENAI_API_VERSION").expect("AZURE_OPENAI_API_VERSION not set");
-    let deployment =
-        std::env::var("AZURE_OPENAI_DEPLOYMENT").expect("AZURE_OPENAI_DEPLOYMENT not set");
+impl AzureOpenAIProvider {
+    // `model` is the Azure deployment name; `base_url` the resource endpoint
+    pub fn new(config: ProviderConfig) -> Result<Self, LLMError> {
+        let endpoint = required_env("AZURE_OPENAI_ENDPOINT")?;
+        let deployment = required_env("AZURE_OPENAI_DEPLOYMENT")?;
+        let settings = ProviderSettings::new(&deployment, &endpoint, 8192, config);
+        let api_key = required_env("AZURE_OPENAI_API_KEY")?;
+        let api_version = required_env("AZURE_OPENAI_API_VERSION")?;
+        let client = settings.http_client()?;
+
+        Ok(AzureOpenAIProvider {
+            settings,
+            api_key,
+            api_version,
+            client,
+        })
+    }
 
-    let url = format!(
-        "{}/openai/deployments/{}/chat/completions?api-version={}",
-        endpoint, deployment, api_version
-    );
+    fn request(&self) -> RequestBuilder {
+        let url = format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.settings.base_url, self.settings.model, self.api_version
+        );
 
-    let body = json!({
-        "messages": [
-            {"role": "system", "content": system_prompt},
-            {"role": "user", "content": input}
-        ],
-        "temperature": 0.2
-    });
+        self.client.post(url).header("api-key", &self.api_key)
+    }
+}
 
-    let res = client
-        .post(url)
-        .header("api-key", api_key)
-        .json(&body)
-        .send()
-        .await?;
+#[async_trait]
+impl LLMProvider for AzureOpenAIProvider {
+    fn name(&self) -> &str {
+        "azure"
+    }
 
-    let json: serde_json::Value = res.json().await?;
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
+    }
 
-    let message = json["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("Generated commit message")
-        .to_string();
+    async fn complete(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let body = chat_body(&self.settings, system_prompt, input, false);
+        chat_completion(self.request(), self.settings.timeout, &body).await
+    }
 
-    Ok(message.trim().to_string())
+    async fn complete_stream(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let body = chat_body(&self.settings, system_prompt, input, false);
+        chat_completion_stream(self.request(), self.settings.timeout, &body, on_token).await
+    }
 }
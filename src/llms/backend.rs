@@ -1,61 +1,190 @@
 use std::env;
 
+use super::azure::AzureOpenAIProvider;
+use super::claude::ClaudeProvider;
+use super::error::LLMError;
+use super::fallback::FallbackProvider;
+use super::gemini::GeminiProvider;
+use super::heuristic::HeuristicProvider;
+use super::mock::MockProvider;
+use super::nollm::NoLLMProvider;
+use super::ollama::OllamaProvider;
+use super::openai::OpenAIProvider;
+use super::openai_compatible::OpenAICompatibleProvider;
+use super::replay::{ReplayProvider, DEFAULT_CASSETTE};
+use super::LLMProvider;
 use crate::config::GIT_AI_CONFIG;
+use colored::*;
 
 #[derive(Debug, Clone)]
 pub enum LLMBackend {
     OpenAI,
+    OpenAICompatible,
     Azure,
     Ollama,
     Claude,
     Gemini,
+    Mock,
+    Replay,
+    Heuristic,
     NoLLM,
 }
 
 impl LLMBackend {
-    pub fn detect_backend() -> LLMBackend {
-        // 1. Try from .git-ai file
-        if let Some(config_backend) = &GIT_AI_CONFIG.llm_backend {
-            return parse_backend(config_backend);
+    // Unknown names and missing keys are errors for the caller to report
+    pub fn detect_backend() -> Result<LLMBackend, LLMError> {
+        parse_backend(&configured_backend())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LLMBackend::OpenAI => "openai",
+            LLMBackend::OpenAICompatible => "openai-compatible",
+            LLMBackend::Azure => "azure",
+            LLMBackend::Ollama => "ollama",
+            LLMBackend::Claude => "claude",
+            LLMBackend::Gemini => "gemini",
+            LLMBackend::Mock => "mock",
+            LLMBackend::Replay => "replay",
+            LLMBackend::Heuristic => "heuristic",
+            LLMBackend::NoLLM => "nollm",
+        }
+    }
+
+    // Build the provider with its `[providers.<name>]` settings from .git-ai
+    pub fn build_provider(&self) -> Result<Box<dyn LLMProvider>, LLMError> {
+        let config = GIT_AI_CONFIG.provider(self.name());
+
+        Ok(match self {
+            LLMBackend::OpenAI => Box::new(OpenAIProvider::new(config)?),
+            LLMBackend::OpenAICompatible => Box::new(OpenAICompatibleProvider::new(config)?),
+            LLMBackend::Azure => Box::new(AzureOpenAIProvider::new(config)?),
+            LLMBackend::Ollama => Box::new(OllamaProvider::new(config)?),
+            LLMBackend::Claude => Box::new(ClaudeProvider::new(config)?),
+            LLMBackend::Gemini => Box::new(GeminiProvider::new(config)?),
+            LLMBackend::Mock => Box::new(MockProvider::new(config)),
+            LLMBackend::Replay => {
+                let cassette =
+                    env::var("GIT_AI_CASSETTE").unwrap_or_else(|_| DEFAULT_CASSETTE.to_string());
+                // GIT_AI_RECORD=<backend> records through that backend instead of replaying
+                let recorder = match env::var("GIT_AI_RECORD") {
+                    Ok(target) => match parse_backend(&target)? {
+                        LLMBackend::Replay => {
+                            return Err(LLMError::Config(
+                                "GIT_AI_RECORD can't be replay itself".to_string(),
+                            ))
+                        }
+                        target => Some(target.build_provider()?),
+                    },
+                    Err(_) => None,
+                };
+                Box::new(ReplayProvider::new(config, cassette, recorder)?)
+            }
+            LLMBackend::Heuristic => Box::new(HeuristicProvider::new(config)),
+            LLMBackend::NoLLM => Box::new(NoLLMProvider::default()),
+        })
+    }
+}
+
+fn configured_backend() -> String {
+    // 1. Try from .git-ai file
+    if let Some(config_backend) = &GIT_AI_CONFIG.llm_backend {
+        return config_backend.clone();
+    }
+
+    // 2. Try from env var
+    env::var("GIT_AI_LLM").unwrap_or_else(|_| "nollm".to_string())
+}
+
+// The configured backend followed by the `fallback` list from .git-ai.
+// Entries that can't be set up (missing keys, unknown names) are skipped
+// so a broken primary still falls through to the rest of the chain.
+pub fn build_fallback_chain(fallback: &[String]) -> Result<Box<dyn LLMProvider>, LLMError> {
+    let mut names = vec![configured_backend()];
+    names.extend(fallback.iter().cloned());
+
+    let mut seen = Vec::new();
+    let mut providers = Vec::new();
+    for name in names {
+        let backend = match parse_backend(&name) {
+            Ok(LLMBackend::NoLLM) => continue,
+            Ok(backend) => backend,
+            Err(e) => {
+                println!(
+                    "{}",
+                    format!("⚠️ Skipping '{}' in the fallback chain: {}", name, e).yellow()
+                );
+                continue;
+            }
+        };
+        if seen.contains(&backend.name()) {
+            continue;
         }
+        seen.push(backend.name());
 
-        // 2. Try from env var
-        let backend = env::var("GIT_AI_LLM").unwrap_or_else(|_| "nollm".to_string());
-        parse_backend(&backend)
+        match backend.build_provider() {
+            Ok(provider) => providers.push(provider),
+            Err(e) => println!(
+                "{}",
+                format!(
+                    "⚠️ Skipping '{}' in the fallback chain: {}",
+                    backend.name(),
+                    e
+                )
+                .yellow()
+            ),
+        }
+    }
+
+    match providers.len() {
+        0 => Err(LLMError::Config(
+            "no provider in the fallback chain could be set up".to_string(),
+        )),
+        1 => Ok(providers.remove(0)),
+        _ => Ok(Box::new(FallbackProvider::new(providers))),
     }
 }
 
-fn parse_backend(backend: &str) -> LLMBackend {
-    match backend.to_lowercase().as_str() {
+fn parse_backend(backend: &str) -> Result<LLMBackend, LLMError> {
+    Ok(match backend.to_lowercase().as_str() {
         "openai" => {
-            check_env("OPENAI_API_KEY");
+            check_env("OPENAI_API_KEY")?;
             LLMBackend::OpenAI
         }
+        // base URL is checked when the provider is built, it may come from .git-ai
+        "openai-compatible" | "openai_compatible" => LLMBackend::OpenAICompatible,
         "azure" => {
-            check_env("AZURE_OPENAI_API_KEY");
-            check_env("AZURE_OPENAI_API_VERSION");
-            check_env("AZURE_OPENAI_ENDPOINT");
-            check_env("AZURE_OPENAI_DEPLOYMENT");
+            check_env("AZURE_OPENAI_API_KEY")?;
+            check_env("AZURE_OPENAI_API_VERSION")?;
+            check_env("AZURE_OPENAI_ENDPOINT")?;
+            check_env("AZURE_OPENAI_DEPLOYMENT")?;
             LLMBackend::Azure
         }
         "ollama" => LLMBackend::Ollama,
         "claude" => {
-            check_env("CLAUDE_API_KEY");
+            check_env("CLAUDE_API_KEY")?;
             LLMBackend::Claude
         }
         "gemini" => {
-            check_env("GEMINI_API_KEY");
+            check_env("GEMINI_API_KEY")?;
             LLMBackend::Gemini
         }
+        "mock" => LLMBackend::Mock,
+        "replay" => LLMBackend::Replay,
+        "heuristic" | "offline" => LLMBackend::Heuristic,
         "nollm" => LLMBackend::NoLLM,
         other => {
-            panic!("❌ Unknown LLM backend: '{}'", other);
+            return Err(LLMError::Config(format!("unknown LLM backend '{}'", other)));
         }
-    }
+    })
 }
 
-fn check_env(var_name: &str) {
+fn check_env(var_name: &str) -> Result<(), LLMError> {
     if env::var(var_name).is_err() {
-        panic!("❌ Required env variable '{}' not set!", var_name);
+        return Err(LLMError::Config(format!(
+            "required env variable '{}' not set",
+            var_name
+        )));
     }
+    Ok(())
 }
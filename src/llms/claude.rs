@@ -1,59 +1,501 @@
-use super::LLMProvider;
+use super::error::{check_response, required_env, LLMError};
+use super::stream::{finish, for_each_line, open_stream, sse_data, TokenSink};
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
+use crate::llm_usage;
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde_json::json;
+use std::env;
 
-pub struct ClaudeProvider;
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+pub struct ClaudeProvider {
+    settings: ProviderSettings,
+    api_key: String,
+    client: Client,
+}
+
+impl ClaudeProvider {
+    pub fn new(mut config: ProviderConfig) -> Result<Self, LLMError> {
+        // CLAUDE_MODEL for quick overrides, [providers.claude] model wins
+        if config.model.is_none() {
+            config.model = env::var("CLAUDE_MODEL").ok();
+        }
+        let settings = ProviderSettings::new(
+            "claude-3-opus-20240229",
+            "https://api.anthropic.com/v1",
+            200_000,
+            config,
+        );
+        let api_key = required_env("CLAUDE_API_KEY")?;
+        let client = settings.http_client()?;
+
+        Ok(ClaudeProvider {
+            settings,
+            api_key,
+            client,
+        })
+    }
+
+    fn request(&self) -> RequestBuilder {
+        self.client
+            .post(format!("{}/messages", self.settings.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+    }
+
+    // The Messages API takes the system prompt as a top-level field, only
+    // user/assistant turns go in `messages`
+    fn body(&self, system_prompt: &str, input: &str, stream: bool) -> serde_json::Value {
+        json!({
+            "model": self.settings.model,
+            "system": system_prompt,
+            "messages": [
+                {"role": "user", "content": input}
+            ],
+            "temperature": self.settings.temperature,
+            "max_tokens": self.settings.max_tokens,
+            "stream": stream
+        })
+    }
+
+    // A cut-off or refused answer isn't a usable commit message
+    fn check_stop_reason(&self, stop_reason: Option<&str>) -> Result<(), LLMError> {
+        match stop_reason {
+            Some("max_tokens") => Err(LLMError::MalformedResponse(format!(
+                "answer was cut off at max_tokens = {}, raise it in [providers.claude]",
+                self.settings.max_tokens
+            ))),
+            Some("refusal") => Err(LLMError::MalformedResponse(
+                "the model declined to answer".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
 
 #[async_trait]
 impl LLMProvider for ClaudeProvider {
-    async fn generate_commit_message(
-        diff: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(
-            diff,
-            "You are a Git commit message generator. Write clear, concise Git commit messages.",
-        )
-        .await
+    fn name(&self) -> &str {
+        "claude"
+    }
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
     }
-    async fn generate_project_scaffolding(
-        prompt: &str,
+
+    async fn complete(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
         input: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(input, prompt).await
+    ) -> Result<String, LLMError> {
+        let res = self
+            .request()
+            .timeout(self.settings.timeout)
+            .json(&self.body(system_prompt, input, false))
+            .send()
+            .await?;
+
+        let json: serde_json::Value = check_response(res).await?.json().await?;
+        self.check_stop_reason(json["stop_reason"].as_str())?;
+
+        // Content is a list of blocks; only text blocks make up the answer
+        let message: String = json["content"]
+            .as_array()
+            .ok_or_else(|| LLMError::MalformedResponse("missing /content".to_string()))?
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+
+        if let (Some(input), Some(output)) = (
+            json["usage"]["input_tokens"].as_u64(),
+            json["usage"]["output_tokens"].as_u64(),
+        ) {
+            llm_usage::report_tokens(input, output);
+        }
+
+        finish(message)
     }
-}
 
-async fn call(
-    input: &str,
-    system_prompt: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client = Client::new();
-    let api_key = std::env::var("CLAUDE_API_KEY").expect("CLAUDE_API_KEY not set");
-
-    let body = json!({
-        "model": "claude-3-opus-20240229",
-        "messages": [
-                    {"role": "system", "content": system_prompt},
-
-            {"role": "user", "content": input}
-        ],
-        "temperature": 0.2
-    });
-
-    let res = client
-        .post("https://api.anthropic.com/v1/messages")
-        .bearer_auth(api_key)
-        .json(&body)
-        .send()
+    async fn complete_stream(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let res = open_stream(
+            self.request().json(&self.body(system_prompt, input, true)),
+            self.settings.timeout,
+        )
+        .await?;
+
+        // Input tokens arrive with message_start, output tokens and the stop
+        // reason with message_delta. Errors can also show up mid-stream.
+        let mut message = String::new();
+        let (mut input_tokens, mut output_tokens) = (None, None);
+        let mut stop_reason = None;
+        let mut stream_error = None;
+        for_each_line(res, |line| {
+            if let Some(event) = sse_data(line) {
+                match event["type"].as_str() {
+                    Some("content_block_delta") => {
+                        if let Some(token) = event["delta"]["text"].as_str() {
+                            on_token(token);
+                            message.push_str(token);
+                        }
+                    }
+                    Some("message_start") => {
+                        input_tokens = event["message"]["usage"]["input_tokens"].as_u64();
+                    }
+                    Some("message_delta") => {
+                        output_tokens = event["usage"]["output_tokens"].as_u64();
+                        stop_reason = event["delta"]["stop_reason"].as_str().map(str::to_string);
+                    }
+                    Some("error") => {
+                        stream_error = Some(stream_error_to_llm_error(&event["error"]));
+                    }
+                    _ => {}
+                }
+            }
+        })
         .await?;
 
-    let json: serde_json::Value = res.json().await?;
+        if let Some(err) = stream_error {
+            return Err(err);
+        }
+        self.check_stop_reason(stop_reason.as_deref())?;
+        if let (Some(input), Some(output)) = (input_tokens, output_tokens) {
+            llm_usage::report_tokens(input, output);
+        }
 
-    let message = json["content"][0]["text"]
+        finish(message)
+    }
+}
+
+// Mid-stream errors carry the same {"type", "message"} body as HTTP errors
+fn stream_error_to_llm_error(error: &serde_json::Value) -> LLMError {
+    let message = error["message"]
         .as_str()
-        .unwrap_or("Generated commit message")
+        .unwrap_or("unknown error")
         .to_string();
 
-    Ok(message.trim().to_string())
+    match error["type"].as_str() {
+        Some("overloaded_error") => LLMError::Api {
+            status: 529,
+            message,
+        },
+        Some("rate_limit_error") => LLMError::RateLimited {
+            message,
+            retry_after: None,
+        },
+        Some("authentication_error") | Some("permission_error") => LLMError::Auth(message),
+        _ => LLMError::Api {
+            status: 500,
+            message,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    // Answers one request with `status` and `body`, hands back what was sent
+    fn serve_once(
+        status: &str,
+        content_type: &str,
+        extra_headers: &str,
+        body: &str,
+    ) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let response = format!(
+            "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            extra_headers,
+            body
+        );
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let request = read_request(&mut reader);
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            request
+        });
+
+        (base_url, handle)
+    }
+
+    // Streams `events` as SSE with `pause` before each one
+    fn serve_slowly(events: Vec<serde_json::Value>, pause: Duration) -> (String, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            read_request(&mut reader);
+            let stream = reader.get_mut();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n")
+                .unwrap();
+            for event in events {
+                thread::sleep(pause);
+                let frame = format!("event: {}\ndata: {}\n\n", event["type"], event);
+                stream.write_all(frame.as_bytes()).unwrap();
+                stream.flush().unwrap();
+            }
+        });
+
+        (base_url, handle)
+    }
+
+    fn read_request(reader: &mut BufReader<TcpStream>) -> String {
+        let mut request = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap();
+            }
+            request.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        request.push_str(&String::from_utf8_lossy(&body));
+        request
+    }
+
+    fn provider(base_url: &str) -> ClaudeProvider {
+        let config = ProviderConfig {
+            base_url: Some(base_url.to_string()),
+            max_tokens: Some(256),
+            ..Default::default()
+        };
+        ClaudeProvider {
+            settings: ProviderSettings::new("claude-test", "", 200_000, config),
+            api_key: "test-key".to_string(),
+            client: Client::new(),
+        }
+    }
+
+    fn request_body(request: &str) -> serde_json::Value {
+        let (_, body) = request.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    async fn complete(status: &str, extra_headers: &str, body: &str) -> Result<String, LLMError> {
+        let (base_url, server) = serve_once(status, "application/json", extra_headers, body);
+        let result = provider(&base_url)
+            .complete(CompletionKind::plain("commit"), "system text", "diff text")
+            .await;
+        server.join().unwrap();
+        result
+    }
+
+    async fn complete_stream(events: &[serde_json::Value]) -> Result<String, LLMError> {
+        let body: String = events
+            .iter()
+            .map(|event| format!("event: {}\ndata: {}\n\n", event["type"], event))
+            .collect();
+        let (base_url, server) = serve_once("200 OK", "text/event-stream", "", &body);
+        let result = provider(&base_url)
+            .complete_stream(
+                CompletionKind::plain("commit"),
+                "system text",
+                "diff text",
+                &mut |_| {},
+            )
+            .await;
+        server.join().unwrap();
+        result
+    }
+
+    #[tokio::test]
+    async fn sends_messages_api_request() {
+        let answer = json!({
+            "content": [{"type": "text", "text": "feat: add x"}],
+            "stop_reason": "end_turn"
+        });
+        let (base_url, server) = serve_once("200 OK", "application/json", "", &answer.to_string());
+        provider(&base_url)
+            .complete(CompletionKind::plain("commit"), "system text", "diff text")
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+
+        assert!(request.starts_with("POST /v1/messages "));
+        let headers = request.to_lowercase();
+        assert!(headers.contains("x-api-key: test-key\r\n"));
+        assert!(headers.contains(&format!("anthropic-version: {}\r\n", ANTHROPIC_VERSION)));
+
+        let body = request_body(&request);
+        assert_eq!(body["system"], "system text");
+        assert_eq!(body["max_tokens"], 256);
+        assert_eq!(
+            body["messages"],
+            json!([{"role": "user", "content": "diff text"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn joins_text_blocks() {
+        let answer = json!({
+            "content": [
+                {"type": "text", "text": "feat: add x\n\n"},
+                {"type": "tool_use", "id": "t1", "name": "noop", "input": {}},
+                {"type": "text", "text": "Explain why."}
+            ],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        });
+
+        let message = complete("200 OK", "", &answer.to_string()).await.unwrap();
+        assert_eq!(message, "feat: add x\n\nExplain why.");
+    }
+
+    #[tokio::test]
+    async fn max_tokens_and_refusal_stop_reasons_are_errors() {
+        for stop_reason in ["max_tokens", "refusal"] {
+            let answer = json!({
+                "content": [{"type": "text", "text": "feat: add"}],
+                "stop_reason": stop_reason
+            });
+            let result = complete("200 OK", "", &answer.to_string()).await;
+            assert!(
+                matches!(result, Err(LLMError::MalformedResponse(_))),
+                "{}: {:?}",
+                stop_reason,
+                result
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn http_errors_map_to_typed_errors() {
+        let unauthorized = json!({
+            "type": "error",
+            "error": {"type": "authentication_error", "message": "invalid x-api-key"}
+        });
+        let result = complete("401 Unauthorized", "", &unauthorized.to_string()).await;
+        assert!(matches!(result, Err(LLMError::Auth(message)) if message == "invalid x-api-key"));
+
+        let rate_limited = json!({
+            "type": "error",
+            "error": {"type": "rate_limit_error", "message": "slow down"}
+        });
+        let result = complete(
+            "429 Too Many Requests",
+            "retry-after: 7\r\n",
+            &rate_limited.to_string(),
+        )
+        .await;
+        match result {
+            Err(LLMError::RateLimited {
+                message,
+                retry_after,
+            }) => {
+                assert_eq!(message, "slow down");
+                assert_eq!(retry_after, Some(std::time::Duration::from_secs(7)));
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_text_deltas() {
+        let message = complete_stream(&[
+            json!({"type": "message_start", "message": {"usage": {"input_tokens": 3}}}),
+            json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "fix: "}}),
+            json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "typo"}}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 2}}),
+            json!({"type": "message_stop"}),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(message, "fix: typo");
+    }
+
+    #[tokio::test]
+    async fn stream_error_events_map_to_typed_errors() {
+        let stream_error = |kind: &str| {
+            vec![
+                json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "fix"}}),
+                json!({"type": "error", "error": {"type": kind, "message": "mid-stream"}}),
+            ]
+        };
+
+        let result = complete_stream(&stream_error("overloaded_error")).await;
+        assert!(matches!(result, Err(LLMError::Api { status: 529, .. })));
+
+        let result = complete_stream(&stream_error("rate_limit_error")).await;
+        assert!(matches!(result, Err(LLMError::RateLimited { .. })));
+
+        let result = complete_stream(&stream_error("authentication_error")).await;
+        assert!(matches!(result, Err(LLMError::Auth(_))));
+
+        let result = complete_stream(&stream_error("api_error")).await;
+        assert!(matches!(result, Err(LLMError::Api { status: 500, .. })));
+    }
+
+    #[tokio::test]
+    async fn stream_stop_reason_max_tokens_is_an_error() {
+        let result = complete_stream(&[
+            json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "fix: cut"}}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "max_tokens"}, "usage": {"output_tokens": 256}}),
+        ])
+        .await;
+        assert!(matches!(result, Err(LLMError::MalformedResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn long_streams_are_not_cut_off_by_timeout_secs() {
+        let (base_url, server) = serve_slowly(
+            vec![
+                json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "fix: "}}),
+                json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "slow"}}),
+                json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": " stream"}}),
+                json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}}),
+            ],
+            Duration::from_millis(400),
+        );
+        let config = ProviderConfig {
+            base_url: Some(base_url),
+            timeout_secs: Some(1),
+            ..Default::default()
+        };
+        let settings = ProviderSettings::new("claude-test", "", 200_000, config);
+        let provider = ClaudeProvider {
+            client: settings.http_client().unwrap(),
+            settings,
+            api_key: "test-key".to_string(),
+        };
+
+        let message = provider
+            .complete_stream(
+                CompletionKind::plain("commit"),
+                "system text",
+                "diff text",
+                &mut |_| {},
+            )
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(message, "fix: slow stream");
+    }
 }
@@ -0,0 +1,145 @@
+use reqwest::{Response, StatusCode};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum LLMError {
+    Auth(String),
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    Quota(String),
+    MalformedResponse(String),
+    Timeout,
+    Network(String),
+    Api {
+        status: u16,
+        message: String,
+    },
+    Config(String),
+    Cancelled,
+    NoBackend,
+    BudgetExceeded {
+        spent: f64,
+        budget: f64,
+    },
+}
+
+impl LLMError {
+    // Worth another attempt: the same request may succeed a bit later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LLMError::RateLimited { .. } | LLMError::Timeout | LLMError::Network(_) => true,
+            LLMError::Api { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LLMError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LLMError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LLMError::Auth(message) => {
+                write!(f, "authentication failed, check your API key ({})", message)
+            }
+            LLMError::RateLimited { message, .. } => write!(f, "rate limited ({})", message),
+            LLMError::Quota(message) => write!(f, "quota or billing limit reached ({})", message),
+            LLMError::MalformedResponse(message) => {
+                write!(f, "unexpected response from backend ({})", message)
+            }
+            LLMError::Timeout => write!(f, "request timed out"),
+            LLMError::Network(message) => write!(f, "network error ({})", message),
+            LLMError::Api { status, message } => write!(f, "HTTP {}: {}", status, message),
+            LLMError::Config(message) => write!(f, "configuration error ({})", message),
+            LLMError::Cancelled => write!(f, "request cancelled"),
+            LLMError::NoBackend => write!(f, "no_llm is set"),
+            LLMError::BudgetExceeded { spent, budget } => write!(
+                f,
+                "monthly budget of ${:.2} reached (${:.2} spent, see `git-ai usage`)",
+                budget, spent
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LLMError {}
+
+impl From<reqwest::Error> for LLMError {
+    fn from(err: reqwest::Error) -> Self {
+        // The URL can carry credentials, keep it out of terminals and CI logs
+        let err = err.without_url();
+        if err.is_timeout() {
+            LLMError::Timeout
+        } else if err.is_decode() {
+            LLMError::MalformedResponse(err.to_string())
+        } else {
+            LLMError::Network(err.to_string())
+        }
+    }
+}
+
+pub(super) fn required_env(name: &str) -> Result<String, LLMError> {
+    std::env::var(name).map_err(|_| LLMError::Config(format!("{} not set", name)))
+}
+
+// Turn non-2xx responses into typed errors, pass successful ones through
+pub(super) async fn check_response(res: Response) -> Result<Response, LLMError> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    let retry_after = res
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let body = res.text().await.unwrap_or_default();
+    let message = error_message(&body);
+
+    Err(match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => LLMError::Auth(message),
+        StatusCode::PAYMENT_REQUIRED => LLMError::Quota(message),
+        // OpenAI reports exhausted credits as a 429 that will never clear
+        StatusCode::TOO_MANY_REQUESTS if body.contains("insufficient_quota") => {
+            LLMError::Quota(message)
+        }
+        StatusCode::TOO_MANY_REQUESTS => LLMError::RateLimited {
+            message,
+            retry_after,
+        },
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => LLMError::Timeout,
+        _ => LLMError::Api {
+            status: status.as_u16(),
+            message,
+        },
+    })
+}
+
+// Providers nest the reason differently: {"error": {"message"}}, {"error": "..."}
+fn error_message(body: &str) -> String {
+    let json: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+
+    json["error"]["message"]
+        .as_str()
+        .or_else(|| json["error"].as_str())
+        .or_else(|| json["message"].as_str())
+        .map(|message| message.to_string())
+        .unwrap_or_else(|| body.trim().chars().take(200).collect())
+}
+
+// Pull a string out of a response or report which field was missing
+pub(super) fn text_at<'a>(json: &'a serde_json::Value, pointer: &str) -> Result<&'a str, LLMError> {
+    json.pointer(pointer)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| LLMError::MalformedResponse(format!("missing {}", pointer)))
+}
@@ -0,0 +1,139 @@
+use super::error::LLMError;
+use super::stream::TokenSink;
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use async_trait::async_trait;
+use colored::*;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Tries each provider in order until one answers, e.g.
+// azure → openai → ollama → heuristic. Each plain call is bounded by that
+// provider's own `timeout_secs`.
+pub struct FallbackProvider {
+    providers: Vec<Box<dyn LLMProvider>>,
+    // Index of the provider that produced the last answer
+    answered: AtomicUsize,
+}
+
+impl FallbackProvider {
+    pub fn new(providers: Vec<Box<dyn LLMProvider>>) -> Self {
+        FallbackProvider {
+            providers,
+            answered: AtomicUsize::new(0),
+        }
+    }
+
+    fn report(&self, index: usize, err: &LLMError) {
+        let failed = self.providers[index].label();
+        let message = match self.providers.get(index + 1) {
+            Some(next) => format!("⚠️ {} failed: {}. Trying {}...", failed, err, next.label()),
+            None => format!("⚠️ {} failed: {}", failed, err),
+        };
+        println!("{}", message.yellow());
+    }
+}
+
+// A hung provider shouldn't hold up the rest of the chain
+async fn bounded<T>(
+    provider: &dyn LLMProvider,
+    call: impl Future<Output = Result<T, LLMError>>,
+) -> Result<T, LLMError> {
+    tokio::time::timeout(provider.settings().timeout, call)
+        .await
+        .unwrap_or(Err(LLMError::Timeout))
+}
+
+#[async_trait]
+impl LLMProvider for FallbackProvider {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    // The primary's settings drive prompt budgets
+    fn settings(&self) -> &ProviderSettings {
+        self.providers[0].settings()
+    }
+
+    fn label(&self) -> String {
+        self.providers
+            .iter()
+            .map(|provider| provider.label())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+
+    fn answered_by(&self) -> (&str, &str) {
+        self.providers[self.answered.load(Ordering::Relaxed)].answered_by()
+    }
+
+    fn fell_back(&self) -> bool {
+        self.answered.load(Ordering::Relaxed) != 0
+    }
+
+    async fn complete(
+        &self,
+        kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let mut last_error = LLMError::NoBackend;
+
+        for (index, provider) in self.providers.iter().enumerate() {
+            match bounded(
+                provider.as_ref(),
+                provider.complete(kind, system_prompt, input),
+            )
+            .await
+            {
+                Ok(message) => {
+                    self.answered.store(index, Ordering::Relaxed);
+                    return Ok(message);
+                }
+                Err(err) => {
+                    self.report(index, &err);
+                    last_error = err;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn complete_stream(
+        &self,
+        kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let mut last_error = LLMError::NoBackend;
+
+        for (index, provider) in self.providers.iter().enumerate() {
+            let mut streamed = false;
+            // Bounded by the provider's own header and between-chunk limits,
+            // a long answer that keeps arriving is not cut off
+            let result = provider
+                .complete_stream(kind, system_prompt, input, &mut |token| {
+                    streamed = true;
+                    on_token(token);
+                })
+                .await;
+
+            match result {
+                Ok(message) => {
+                    self.answered.store(index, Ordering::Relaxed);
+                    return Ok(message);
+                }
+                // Half of an answer is already on screen, another provider
+                // would only append a second one
+                Err(err) if streamed => return Err(err),
+                Err(err) => {
+                    self.report(index, &err);
+                    last_error = err;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+}
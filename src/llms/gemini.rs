@@ -1,56 +1,130 @@
-use super::LLMProvider;
+use super::error::{check_response, required_env, text_at, LLMError};
+use super::stream::{finish, for_each_line, open_stream, sse_data, TokenSink};
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
+use crate::llm_usage;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
 
-pub struct GeminiProvider;
+pub struct GeminiProvider {
+    settings: ProviderSettings,
+    api_key: String,
+    client: Client,
+}
+
+impl GeminiProvider {
+    pub fn new(config: ProviderConfig) -> Result<Self, LLMError> {
+        let settings = ProviderSettings::new(
+            "gemini-1.5-pro",
+            "https://generativelanguage.googleapis.com/v1beta",
+            1_000_000,
+            config,
+        );
+        let api_key = required_env("GEMINI_API_KEY")?;
+        let client = settings.http_client()?;
+
+        Ok(GeminiProvider {
+            settings,
+            api_key,
+            client,
+        })
+    }
+
+    fn body(&self, system_prompt: &str, input: &str) -> serde_json::Value {
+        json!({
+            "contents": [{
+                "role": "user",
+                "parts": [{
+                    "text": format!("{}\n{}", system_prompt, input)
+                }]
+            }],
+            "generationConfig": {
+                "temperature": self.settings.temperature,
+                "maxOutputTokens": self.settings.max_tokens
+            }
+        })
+    }
+}
 
 #[async_trait]
 impl LLMProvider for GeminiProvider {
-    async fn generate_commit_message(
-        diff: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(
-            diff,
-            "You are a Git commit message generator. Write clear, concise Git commit messages.",
-        )
-        .await
+    fn name(&self) -> &str {
+        "gemini"
     }
-    async fn generate_project_scaffolding(
-        prompt: &str,
-        input: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(input, prompt).await
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
     }
-}
 
-async fn call(
-    input: &str,
-    system_prompt: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client = Client::new();
-    let api_key = std::env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY not set");
-
-    let body = json!({
-        "contents": [{
-            "role": "user",
-            "parts": [{
-                "text": format!("{}\n{}",system_prompt,input)
-            }]
-        }]
-    });
-
-    let res = client.post(format!("https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-pro:generateContent?key={}", api_key))
-            .json(&body)
+    async fn complete(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let res = self
+            .client
+            .post(format!(
+                "{}/models/{}:generateContent",
+                self.settings.base_url, self.settings.model
+            ))
+            .timeout(self.settings.timeout)
+            .header("x-goog-api-key", &self.api_key)
+            .json(&self.body(system_prompt, input))
             .send()
             .await?;
 
-    let json: serde_json::Value = res.json().await?;
+        let json: serde_json::Value = check_response(res).await?.json().await?;
+        let message = text_at(&json, "/candidates/0/content/parts/0/text")?;
+        report_usage(&json);
+
+        Ok(message.trim().to_string())
+    }
+
+    async fn complete_stream(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let res = open_stream(
+            self.client
+                .post(format!(
+                    "{}/models/{}:streamGenerateContent?alt=sse",
+                    self.settings.base_url, self.settings.model
+                ))
+                .header("x-goog-api-key", &self.api_key)
+                .json(&self.body(system_prompt, input)),
+            self.settings.timeout,
+        )
+        .await?;
+
+        let mut message = String::new();
+        for_each_line(res, |line| {
+            if let Some(event) = sse_data(line) {
+                if let Some(token) = event["candidates"][0]["content"]["parts"][0]["text"].as_str()
+                {
+                    on_token(token);
+                    message.push_str(token);
+                }
+                // Every chunk repeats the running totals, the last one wins
+                report_usage(&event);
+            }
+        })
+        .await?;
 
-    let message = json["candidates"][0]["content"]["parts"][0]["text"]
-        .as_str()
-        .unwrap_or("Generated commit message")
-        .to_string();
+        finish(message)
+    }
+}
 
-    Ok(message.trim().to_string())
+fn report_usage(json: &serde_json::Value) {
+    let metadata = &json["usageMetadata"];
+    if let Some(input) = metadata["promptTokenCount"].as_u64() {
+        llm_usage::report_tokens(
+            input,
+            metadata["candidatesTokenCount"].as_u64().unwrap_or(0),
+        );
+    }
 }
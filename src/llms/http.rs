@@ -0,0 +1,112 @@
+use super::error::LLMError;
+use crate::config::GIT_AI_CONFIG;
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 30;
+
+// Every provider gets its client from here so proxy, CA and timeout
+// settings from the `[http]` table in .git-ai apply everywhere. There is no
+// overall request limit on the client: plain calls set `timeout_secs` per
+// request, streamed ones are bounded by the pause between chunks.
+pub fn client() -> Result<Client, LLMError> {
+    let config = GIT_AI_CONFIG.http.clone().unwrap_or_default();
+
+    let user_agent = config
+        .user_agent
+        .unwrap_or_else(|| format!("git-ai/{}", env!("CARGO_PKG_VERSION")));
+    let connect_timeout = Duration::from_secs(
+        config
+            .connect_timeout_secs
+            .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+    );
+
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .connect_timeout(connect_timeout);
+
+    for proxy in proxies(config.proxy, config.no_proxy)? {
+        builder = builder.proxy(proxy);
+    }
+
+    for certificate in extra_root_certificates(config.ca_bundle)? {
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    builder
+        .build()
+        .map_err(|e| LLMError::Config(format!("failed to set up HTTP client: {}", e)))
+}
+
+// Without `proxy` or `no_proxy` reqwest already honors HTTPS_PROXY,
+// HTTP_PROXY, ALL_PROXY and NO_PROXY from the environment. `no_proxy` from
+// .git-ai replaces NO_PROXY, so the environment proxies are set up here.
+fn proxies(proxy: Option<String>, no_proxy: Option<String>) -> Result<Vec<Proxy>, LLMError> {
+    let invalid =
+        |url: &str, e: reqwest::Error| LLMError::Config(format!("invalid proxy '{}': {}", url, e));
+    let config_no_proxy = no_proxy.as_deref().and_then(NoProxy::from_string);
+
+    if let Some(url) = proxy {
+        let proxy = Proxy::all(&url)
+            .map_err(|e| invalid(&url, e))?
+            .no_proxy(config_no_proxy.or_else(NoProxy::from_env));
+        return Ok(vec![proxy]);
+    }
+    if config_no_proxy.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let from_env = |names: [&str; 2]| {
+        names
+            .iter()
+            .find_map(|name| env::var(name).ok())
+            .filter(|url| !url.is_empty())
+    };
+    let mut proxies = Vec::new();
+    for (scheme, names) in [
+        ("https", ["HTTPS_PROXY", "https_proxy"]),
+        ("http", ["HTTP_PROXY", "http_proxy"]),
+        ("all", ["ALL_PROXY", "all_proxy"]),
+    ] {
+        let Some(url) = from_env(names) else {
+            continue;
+        };
+        let proxy = match scheme {
+            "https" => Proxy::https(&url),
+            "http" => Proxy::http(&url),
+            _ => Proxy::all(&url),
+        };
+        proxies.push(
+            proxy
+                .map_err(|e| invalid(&url, e))?
+                .no_proxy(config_no_proxy.clone()),
+        );
+    }
+    Ok(proxies)
+}
+
+// Longest silence tolerated between chunks of a streamed response
+pub fn read_timeout() -> Duration {
+    let secs = GIT_AI_CONFIG
+        .http
+        .as_ref()
+        .and_then(|http| http.read_timeout_secs)
+        .unwrap_or(DEFAULT_READ_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+// Corporate CA bundle from `ca_bundle`, or GIT_AI_CA_BUNDLE, trusted on
+// top of the system roots
+fn extra_root_certificates(ca_bundle: Option<String>) -> Result<Vec<Certificate>, LLMError> {
+    let Some(path) = ca_bundle.or_else(|| env::var("GIT_AI_CA_BUNDLE").ok()) else {
+        return Ok(Vec::new());
+    };
+
+    let pem = fs::read(&path)
+        .map_err(|e| LLMError::Config(format!("can't read CA bundle {}: {}", path, e)))?;
+    Certificate::from_pem_bundle(&pem)
+        .map_err(|e| LLMError::Config(format!("invalid CA bundle {}: {}", path, e)))
+}
@@ -0,0 +1,69 @@
+use super::error::LLMError;
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::commit_message::{extract_json_object, CommitMessage};
+use crate::config::ProviderConfig;
+use async_trait::async_trait;
+use std::env;
+
+const DEFAULT_TEMPLATE: &str = "chore: update {files}";
+
+// Offline provider answering from a template, for CI and demos.
+// Placeholders: {files}, {file_count}, {lines}, {first_line}
+pub struct MockProvider {
+    settings: ProviderSettings,
+    template: String,
+}
+
+impl MockProvider {
+    pub fn new(config: ProviderConfig) -> Self {
+        MockProvider {
+            settings: ProviderSettings::new("mock", "", 8192, config),
+            template: env::var("GIT_AI_MOCK_RESPONSE").unwrap_or_else(|_| DEFAULT_TEMPLATE.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
+    }
+
+    async fn complete(
+        &self,
+        kind: CompletionKind<'_>,
+        _system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let response = render(&self.template, input);
+        // Structured commits expect JSON; a plain template answers in that
+        // shape, a JSON one is sent as written
+        if kind.structured && extract_json_object(&response).is_none() {
+            return Ok(CommitMessage::from_plain(&response).to_json());
+        }
+        Ok(response)
+    }
+}
+
+fn render(template: &str, input: &str) -> String {
+    let files: Vec<&str> = input
+        .lines()
+        .filter_map(|line| line.strip_prefix("diff --git a/"))
+        .filter_map(|rest| rest.split(" b/").next())
+        .collect();
+    let files_text = if files.is_empty() {
+        "files".to_string()
+    } else {
+        files.join(", ")
+    };
+
+    template
+        .replace("{files}", &files_text)
+        .replace("{file_count}", &files.len().to_string())
+        .replace("{lines}", &input.lines().count().to_string())
+        .replace("{first_line}", input.lines().next().unwrap_or_default())
+}
@@ -1,19 +1,136 @@
 pub mod azure;
 pub mod backend;
 pub mod claude;
+pub mod error;
+pub mod fallback;
 pub mod gemini;
+pub mod heuristic;
+pub mod http;
+pub mod mock;
+pub mod nollm;
 pub mod ollama;
 pub mod openai;
+pub mod openai_compatible;
+pub mod replay;
+pub mod stream;
 
+use crate::config::ProviderConfig;
 use async_trait::async_trait;
+use error::LLMError;
+use std::time::Duration;
+use stream::TokenSink;
+
+pub const COMMIT_SYSTEM_PROMPT: &str =
+    "You are a Git commit message generator. Write clear, concise Git commit messages.";
+
+pub const DIFF_SUMMARY_PROMPT: &str = "You summarize one part of a larger staged Git diff. List the meaningful changes (files, functions, behavior) as short bullet points. Do not write a commit message.";
+
+pub const COMMIT_FROM_SUMMARIES_PROMPT: &str = "You are a Git commit message generator. The staged diff was too large to send at once, so you are given summaries of its parts. Write one clear, concise Git commit message covering all of them.";
+
+// Effective settings for a provider: built-in defaults overridden by .git-ai
+#[derive(Debug, Clone)]
+pub struct ProviderSettings {
+    pub model: String,
+    pub base_url: String,
+    pub temperature: f32,
+    pub max_tokens: u32,
+    pub timeout: Duration,
+    pub context_tokens: usize,
+}
+
+impl ProviderSettings {
+    pub fn new(
+        default_model: &str,
+        default_base_url: &str,
+        default_context_tokens: usize,
+        config: ProviderConfig,
+    ) -> Self {
+        ProviderSettings {
+            model: config.model.unwrap_or_else(|| default_model.to_string()),
+            base_url: config
+                .base_url
+                .unwrap_or_else(|| default_base_url.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            temperature: config.temperature.unwrap_or(0.2),
+            max_tokens: config.max_tokens.unwrap_or(1024),
+            timeout: Duration::from_secs(config.timeout_secs.unwrap_or(60)),
+            context_tokens: config.context_tokens.unwrap_or(default_context_tokens),
+        }
+    }
+
+    pub fn http_client(&self) -> Result<reqwest::Client, LLMError> {
+        http::client()
+    }
+}
+
+// What a request is for: the prompt template it was rendered from (see
+// prompt_templates) and whether the answer must be the JSON commit shape.
+// Offline providers can't read that from a prompt a user may have rewritten.
+#[derive(Clone, Copy, Debug)]
+pub struct CompletionKind<'a> {
+    pub template: &'a str,
+    pub structured: bool,
+}
+
+impl<'a> CompletionKind<'a> {
+    pub fn plain(template: &'a str) -> Self {
+        CompletionKind {
+            template,
+            structured: false,
+        }
+    }
+
+    pub fn structured(template: &'a str) -> Self {
+        CompletionKind {
+            template,
+            structured: true,
+        }
+    }
+}
 
 #[async_trait]
-pub trait LLMProvider {
-    async fn generate_commit_message(
-        diff: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
-    async fn generate_project_scaffolding(
-        prompt: &str,
+pub trait LLMProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn settings(&self) -> &ProviderSettings;
+
+    // e.g. "openai (gpt-4)"
+    fn label(&self) -> String {
+        format!("{} ({})", self.name(), self.settings().model)
+    }
+
+    // (backend, model) that wrote the last answer; differs from this
+    // provider for a fallback chain or a recording replay
+    fn answered_by(&self) -> (&str, &str) {
+        (self.name(), &self.settings().model)
+    }
+
+    // Whether the last answer came from a step after the primary of a
+    // fallback chain; those answers are not cached
+    fn fell_back(&self) -> bool {
+        false
+    }
+
+    // Only offline providers look at `kind`; the others answer the prompt
+    async fn complete(
+        &self,
+        kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError>;
+
+    // Push tokens into `on_token` as they arrive and return the full text.
+    // Providers without streaming support hand over the whole answer at once.
+    async fn complete_stream(
+        &self,
+        kind: CompletionKind<'_>,
+        system_prompt: &str,
         input: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let message = self.complete(kind, system_prompt, input).await?;
+        on_token(&message);
+        Ok(message)
+    }
 }
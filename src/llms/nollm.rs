@@ -0,0 +1,37 @@
+use super::error::LLMError;
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
+use async_trait::async_trait;
+
+// Placeholder provider used when no LLM backend is configured
+pub struct NoLLMProvider {
+    settings: ProviderSettings,
+}
+
+impl Default for NoLLMProvider {
+    fn default() -> Self {
+        NoLLMProvider {
+            settings: ProviderSettings::new("none", "", 0, ProviderConfig::default()),
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for NoLLMProvider {
+    fn name(&self) -> &str {
+        "nollm"
+    }
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
+    }
+
+    async fn complete(
+        &self,
+        _kind: CompletionKind<'_>,
+        _system_prompt: &str,
+        _input: &str,
+    ) -> Result<String, LLMError> {
+        Err(LLMError::NoBackend)
+    }
+}
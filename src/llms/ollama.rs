@@ -1,53 +1,255 @@
-use super::LLMProvider;
+use super::error::{check_response, text_at, LLMError};
+use super::stream::{finish, for_each_line, open_stream, TokenSink};
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
+use crate::llm_usage;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
+use std::env;
+use std::time::Duration;
 
-pub struct OllamaProvider;
+const DEFAULT_HOST: &str = "http://localhost:11434";
+const DEFAULT_PORT: u16 = 11434;
+// Model downloads take minutes, not the usual request timeout
+const PULL_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+
+pub struct OllamaProvider {
+    settings: ProviderSettings,
+    keep_alive: Option<String>,
+    client: Client,
+}
+
+// Installed model as reported by /api/tags
+pub struct LocalModel {
+    pub name: String,
+    pub size: u64,
+}
+
+// Progress line from /api/pull, e.g. "downloading" with byte counts
+pub struct PullProgress {
+    pub status: String,
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+}
+
+impl OllamaProvider {
+    // [providers.ollama] wins over OLLAMA_HOST / OLLAMA_MODEL
+    pub fn new(mut config: ProviderConfig) -> Result<Self, LLMError> {
+        if config.base_url.is_none() {
+            config.base_url = env::var("OLLAMA_HOST")
+                .ok()
+                .map(|host| normalize_host(&host));
+        }
+        if config.model.is_none() {
+            config.model = env::var("OLLAMA_MODEL").ok();
+        }
+        let keep_alive = config.keep_alive.clone();
+        let settings = ProviderSettings::new("mistral", DEFAULT_HOST, 4096, config);
+        let client = settings.http_client()?;
+
+        Ok(OllamaProvider {
+            settings,
+            keep_alive,
+            client,
+        })
+    }
+
+    // num_ctx follows context_tokens so long diffs aren't silently cut to
+    // Ollama's small default window
+    fn body(&self, system_prompt: &str, input: &str, stream: bool) -> serde_json::Value {
+        let mut body = json!({
+            "model": self.settings.model,
+            "messages": [
+                {"role": "system", "content": system_prompt},
+                {"role": "user", "content": input}
+            ],
+            "stream": stream,
+            "options": {
+                "temperature": self.settings.temperature,
+                "num_predict": self.settings.max_tokens,
+                "num_ctx": self.settings.context_tokens
+            }
+        });
+        if let Some(keep_alive) = &self.keep_alive {
+            body["keep_alive"] = json!(keep_alive);
+        }
+        body
+    }
+
+    // Ollama answers 404 for a model that was never pulled
+    fn explain_missing_model(&self, err: LLMError) -> LLMError {
+        match err {
+            LLMError::Api { status: 404, .. } => LLMError::Config(format!(
+                "model '{}' is not installed on {}, run `git-ai models pull {}`",
+                self.settings.model, self.settings.base_url, self.settings.model
+            )),
+            err => err,
+        }
+    }
+
+    pub async fn list_models(&self) -> Result<Vec<LocalModel>, LLMError> {
+        let res = self
+            .client
+            .get(format!("{}/api/tags", self.settings.base_url))
+            .send()
+            .await?;
+        let json: serde_json::Value = check_response(res).await?.json().await?;
+
+        Ok(json["models"]
+            .as_array()
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|model| {
+                        Some(LocalModel {
+                            name: model["name"].as_str()?.to_string(),
+                            size: model["size"].as_u64().unwrap_or(0),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    pub async fn pull_model(
+        &self,
+        name: &str,
+        mut on_progress: impl FnMut(PullProgress) + Send,
+    ) -> Result<(), LLMError> {
+        let res = self
+            .client
+            .post(format!("{}/api/pull", self.settings.base_url))
+            .timeout(PULL_TIMEOUT)
+            .json(&json!({"model": name, "stream": true}))
+            .send()
+            .await?;
+
+        // Failures after the download started come back as {"error": "..."}
+        let mut pull_error = None;
+        for_each_line(res, |line| {
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+                return;
+            };
+            if let Some(error) = event["error"].as_str() {
+                pull_error = Some(error.to_string());
+            } else if let Some(status) = event["status"].as_str() {
+                on_progress(PullProgress {
+                    status: status.to_string(),
+                    completed: event["completed"].as_u64(),
+                    total: event["total"].as_u64(),
+                });
+            }
+        })
+        .await?;
+
+        match pull_error {
+            Some(message) => Err(LLMError::Api {
+                status: 500,
+                message,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+// Installed names carry a tag, "llama3" is stored as "llama3:latest"
+pub fn is_installed(models: &[LocalModel], name: &str) -> bool {
+    models
+        .iter()
+        .any(|model| model.name == name || model.name == format!("{}:latest", name))
+}
+
+// OLLAMA_HOST may be "0.0.0.0", "host:port" or a full URL
+fn normalize_host(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    let with_scheme = if host.contains("://") {
+        host.to_string()
+    } else {
+        format!("http://{}", host)
+    };
+
+    let authority = with_scheme.split("://").nth(1).unwrap_or_default();
+    if authority.contains(':') {
+        with_scheme
+    } else {
+        format!("{}:{}", with_scheme, DEFAULT_PORT)
+    }
+}
 
 #[async_trait]
 impl LLMProvider for OllamaProvider {
-    async fn generate_commit_message(
-        diff: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(
-            diff,
-            "You are a Git commit message generator. Write clear, concise Git commit messages.",
-        )
-        .await
+    fn name(&self) -> &str {
+        "ollama"
     }
-    async fn generate_project_scaffolding(
-        prompt: &str,
-        input: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call(input, prompt).await
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
     }
-}
 
-async fn call(
-    input: &str,
-    system_prompt: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client = Client::new();
+    async fn complete(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let res = self
+            .client
+            .post(format!("{}/api/chat", self.settings.base_url))
+            .timeout(self.settings.timeout)
+            .json(&self.body(system_prompt, input, false))
+            .send()
+            .await?;
 
-    let body = json!({
-        "model": "mistral", // Default local model (user can customize later)
-        "prompt": format!("{}\n {}",system_prompt, input),
-        "stream": false
-    });
+        let res = check_response(res)
+            .await
+            .map_err(|e| self.explain_missing_model(e))?;
+        let json: serde_json::Value = res.json().await?;
+        let message = text_at(&json, "/message/content")?;
+        report_usage(&json);
 
-    let res = client
-        .post("http://localhost:11434/api/generate")
-        .json(&body)
-        .send()
+        Ok(message.trim().to_string())
+    }
+
+    async fn complete_stream(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let res = open_stream(
+            self.client
+                .post(format!("{}/api/chat", self.settings.base_url))
+                .json(&self.body(system_prompt, input, true)),
+            self.settings.timeout,
+        )
         .await?;
 
-    let json: serde_json::Value = res.json().await?;
+        // NDJSON: one {"message": {"content": "..."}, "done": false} object per line
+        let mut message = String::new();
+        for_each_line(res, |line| {
+            if let Ok(event) = serde_json::from_str::<serde_json::Value>(line) {
+                if let Some(token) = event["message"]["content"].as_str() {
+                    on_token(token);
+                    message.push_str(token);
+                }
+                // The final {"done": true} object carries the counts
+                report_usage(&event);
+            }
+        })
+        .await
+        .map_err(|e| self.explain_missing_model(e))?;
 
-    let message = json["response"]
-        .as_str()
-        .unwrap_or("Generated commit message")
-        .to_string();
+        finish(message)
+    }
+}
 
-    Ok(message.trim().to_string())
+fn report_usage(json: &serde_json::Value) {
+    if let (Some(input), Some(output)) = (
+        json["prompt_eval_count"].as_u64(),
+        json["eval_count"].as_u64(),
+    ) {
+        llm_usage::report_tokens(input, output);
+    }
 }
@@ -1,56 +1,142 @@
-use super::LLMProvider;
+use super::error::{check_response, required_env, text_at, LLMError};
+use super::stream::{finish, for_each_line, open_stream, sse_data, TokenSink};
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
+use crate::llm_usage;
 use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde_json::json;
+use std::time::Duration;
 
-pub struct OpenAIProvider;
+pub struct OpenAIProvider {
+    settings: ProviderSettings,
+    api_key: String,
+    client: Client,
+}
+
+impl OpenAIProvider {
+    pub fn new(config: ProviderConfig) -> Result<Self, LLMError> {
+        let settings = ProviderSettings::new("gpt-4", "https://api.openai.com/v1", 8192, config);
+        let api_key = required_env("OPENAI_API_KEY")?;
+        let client = settings.http_client()?;
+
+        Ok(OpenAIProvider {
+            settings,
+            api_key,
+            client,
+        })
+    }
+
+    fn request(&self) -> RequestBuilder {
+        self.client
+            .post(format!("{}/chat/completions", self.settings.base_url))
+            .bearer_auth(&self.api_key)
+    }
+}
 
 #[async_trait]
 impl LLMProvider for OpenAIProvider {
-    async fn generate_commit_message(
-        diff: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call_openai_api(
-            diff,
-            "You are a Git commit message generator. Write clear, concise Git commit messages.",
-        )
-        .await
-    }
-
-    async fn generate_project_scaffolding(
-        prompt: &str,
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
+    }
+
+    async fn complete(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let body = chat_body(&self.settings, system_prompt, input, true);
+        chat_completion(self.request(), self.settings.timeout, &body).await
+    }
+
+    async fn complete_stream(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
         input: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        call_openai_api(input, prompt).await
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let mut body = chat_body(&self.settings, system_prompt, input, true);
+        // Ask for a final chunk carrying token usage
+        body["stream_options"] = json!({"include_usage": true});
+        chat_completion_stream(self.request(), self.settings.timeout, &body, on_token).await
     }
 }
 
-async fn call_openai_api(
-    input: &str,
+// Shared chat-completions body for OpenAI, Azure and OpenAI-compatible servers.
+// Azure selects the model through the deployment URL, so it skips `model`.
+pub(super) fn chat_body(
+    settings: &ProviderSettings,
     system_prompt: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::new();
-    let api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY not set");
-
-    let body = serde_json::json!({
-        "model": "gpt-4",
+    input: &str,
+    send_model: bool,
+) -> serde_json::Value {
+    let mut body = json!({
         "messages": [
             {"role": "system", "content": system_prompt},
             {"role": "user", "content": input}
         ],
-        "temperature": 0.2
+        "temperature": settings.temperature,
+        "max_tokens": settings.max_tokens
     });
+    if send_model {
+        body["model"] = json!(settings.model);
+    }
+    body
+}
 
-    let res = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .bearer_auth(api_key)
-        .json(&body)
-        .send()
-        .await?;
+pub(super) async fn chat_completion(
+    request: RequestBuilder,
+    timeout: Duration,
+    body: &serde_json::Value,
+) -> Result<String, LLMError> {
+    let res = check_response(request.timeout(timeout).json(body).send().await?).await?;
 
     let json: serde_json::Value = res.json().await?;
-    let message = json["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("Generated message")
-        .to_string();
+    let message = text_at(&json, "/choices/0/message/content")?;
+    report_usage(&json);
 
     Ok(message.trim().to_string())
 }
+
+// Same call with `stream: true`, reading the SSE deltas
+pub(super) async fn chat_completion_stream(
+    request: RequestBuilder,
+    timeout: Duration,
+    body: &serde_json::Value,
+    on_token: TokenSink<'_>,
+) -> Result<String, LLMError> {
+    let mut body = body.clone();
+    body["stream"] = json!(true);
+
+    let res = open_stream(request.json(&body), timeout).await?;
+
+    let mut message = String::new();
+    for_each_line(res, |line| {
+        if let Some(event) = sse_data(line) {
+            if let Some(token) = event["choices"][0]["delta"]["content"].as_str() {
+                on_token(token);
+                message.push_str(token);
+            }
+            report_usage(&event);
+        }
+    })
+    .await?;
+
+    finish(message)
+}
+
+fn report_usage(json: &serde_json::Value) {
+    let usage = &json["usage"];
+    if let (Some(input), Some(output)) = (
+        usage["prompt_tokens"].as_u64(),
+        usage["completion_tokens"].as_u64(),
+    ) {
+        llm_usage::report_tokens(input, output);
+    }
+}
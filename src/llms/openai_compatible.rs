@@ -0,0 +1,86 @@
+use super::error::LLMError;
+use super::openai::{chat_body, chat_completion, chat_completion_stream};
+use super::stream::TokenSink;
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use std::env;
+
+// Any server speaking the OpenAI chat-completions protocol
+// (llama.cpp server, vLLM, LM Studio, internal gateways, ...)
+pub struct OpenAICompatibleProvider {
+    settings: ProviderSettings,
+    api_key: Option<String>,
+    client: Client,
+}
+
+impl OpenAICompatibleProvider {
+    pub fn new(mut config: ProviderConfig) -> Result<Self, LLMError> {
+        if config.base_url.is_none() {
+            config.base_url = env::var("OPENAI_COMPATIBLE_BASE_URL").ok();
+        }
+        if config.model.is_none() {
+            config.model = env::var("OPENAI_COMPATIBLE_MODEL").ok();
+        }
+        if config.base_url.is_none() {
+            return Err(LLMError::Config(
+                "openai-compatible needs OPENAI_COMPATIBLE_BASE_URL or [providers.openai-compatible] base_url".to_string(),
+            ));
+        }
+
+        let settings = ProviderSettings::new("default", "", 4096, config);
+        // Local servers usually don't need a key
+        let api_key = env::var("OPENAI_COMPATIBLE_API_KEY").ok();
+        let client = settings.http_client()?;
+
+        Ok(OpenAICompatibleProvider {
+            settings,
+            api_key,
+            client,
+        })
+    }
+
+    fn request(&self) -> RequestBuilder {
+        let request = self
+            .client
+            .post(format!("{}/chat/completions", self.settings.base_url));
+
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for OpenAICompatibleProvider {
+    fn name(&self) -> &str {
+        "openai-compatible"
+    }
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
+    }
+
+    async fn complete(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let body = chat_body(&self.settings, system_prompt, input, true);
+        chat_completion(self.request(), self.settings.timeout, &body).await
+    }
+
+    async fn complete_stream(
+        &self,
+        _kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, LLMError> {
+        let body = chat_body(&self.settings, system_prompt, input, true);
+        chat_completion_stream(self.request(), self.settings.timeout, &body, on_token).await
+    }
+}
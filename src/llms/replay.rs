@@ -0,0 +1,119 @@
+use super::error::LLMError;
+use super::{CompletionKind, LLMProvider, ProviderSettings};
+use crate::config::ProviderConfig;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+
+pub const DEFAULT_CASSETTE: &str = ".git-ai-cassette.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Interaction {
+    system_prompt: String,
+    input: String,
+    response: String,
+}
+
+// Replays recorded request/response pairs from a cassette file. With a
+// recorder attached, forwards requests to it and appends what it answered.
+pub struct ReplayProvider {
+    settings: ProviderSettings,
+    cassette: String,
+    recorder: Option<Box<dyn LLMProvider>>,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl ReplayProvider {
+    pub fn new(
+        config: ProviderConfig,
+        cassette: String,
+        recorder: Option<Box<dyn LLMProvider>>,
+    ) -> Result<Self, LLMError> {
+        let interactions = match fs::read_to_string(&cassette) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                LLMError::Config(format!("cassette {} is not valid: {}", cassette, e))
+            })?,
+            Err(_) if recorder.is_some() => Vec::new(),
+            Err(_) => {
+                return Err(LLMError::Config(format!(
+                    "cassette {} not found, record it first with GIT_AI_RECORD=<backend>",
+                    cassette
+                )))
+            }
+        };
+
+        let settings = match &recorder {
+            Some(recorder) => recorder.settings().clone(),
+            None => ProviderSettings::new("replay", "", 8192, config),
+        };
+
+        Ok(ReplayProvider {
+            settings,
+            cassette,
+            recorder,
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    fn save(&self, interactions: &[Interaction]) -> Result<(), LLMError> {
+        let json = serde_json::to_string_pretty(interactions)
+            .map_err(|e| LLMError::Config(e.to_string()))?;
+        fs::write(&self.cassette, json)
+            .map_err(|e| LLMError::Config(format!("failed to write {}: {}", self.cassette, e)))
+    }
+}
+
+#[async_trait]
+impl LLMProvider for ReplayProvider {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn settings(&self) -> &ProviderSettings {
+        &self.settings
+    }
+
+    fn answered_by(&self) -> (&str, &str) {
+        match &self.recorder {
+            Some(recorder) => recorder.answered_by(),
+            None => (self.name(), &self.settings.model),
+        }
+    }
+
+    async fn complete(
+        &self,
+        kind: CompletionKind<'_>,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<String, LLMError> {
+        let Some(recorder) = &self.recorder else {
+            return self
+                .interactions
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|i| i.system_prompt == system_prompt && i.input == input)
+                .map(|i| i.response.clone())
+                .ok_or_else(|| {
+                    LLMError::Config(format!(
+                        "no recorded response for this request in {}",
+                        self.cassette
+                    ))
+                });
+        };
+
+        let response = recorder.complete(kind, system_prompt, input).await?;
+
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.retain(|i| !(i.system_prompt == system_prompt && i.input == input));
+        interactions.push(Interaction {
+            system_prompt: system_prompt.to_string(),
+            input: input.to_string(),
+            response: response.clone(),
+        });
+        self.save(&interactions)?;
+
+        Ok(response)
+    }
+}
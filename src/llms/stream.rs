@@ -0,0 +1,72 @@
+use super::error::{check_response, LLMError};
+use super::http::read_timeout;
+use reqwest::{RequestBuilder, Response};
+use std::time::Duration;
+
+pub type TokenSink<'a> = &'a mut (dyn FnMut(&str) + Send);
+
+// Send a streaming request. Only the wait for the response headers counts
+// against `timeout`; a long answer that keeps arriving is never cut off,
+// `for_each_line` bounds the pauses between chunks instead.
+pub(super) async fn open_stream(
+    request: RequestBuilder,
+    timeout: Duration,
+) -> Result<Response, LLMError> {
+    Ok(tokio::time::timeout(timeout, request.send())
+        .await
+        .map_err(|_| LLMError::Timeout)??)
+}
+
+// Read a streaming response line by line (NDJSON or SSE framing)
+pub(super) async fn for_each_line(
+    res: Response,
+    mut on_line: impl FnMut(&str) + Send,
+) -> Result<(), LLMError> {
+    let mut res = check_response(res).await?;
+    let mut buffer: Vec<u8> = Vec::new();
+    let read_timeout = read_timeout();
+
+    // A stalled stream counts as a timeout instead of hanging forever
+    while let Some(chunk) = tokio::time::timeout(read_timeout, res.chunk())
+        .await
+        .map_err(|_| LLMError::Timeout)??
+    {
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+            if !line.is_empty() {
+                on_line(line);
+            }
+        }
+    }
+
+    let rest = String::from_utf8_lossy(&buffer);
+    if !rest.trim().is_empty() {
+        on_line(rest.trim());
+    }
+
+    Ok(())
+}
+
+// Payload of an SSE `data:` line, None for comments, event names and [DONE]
+pub(super) fn sse_data(line: &str) -> Option<serde_json::Value> {
+    let data = line.strip_prefix("data:")?.trim();
+    if data == "[DONE]" {
+        return None;
+    }
+    serde_json::from_str(data).ok()
+}
+
+// A stream that ended without any text is as bad as a malformed body
+pub(super) fn finish(message: String) -> Result<String, LLMError> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(LLMError::MalformedResponse(
+            "stream ended without any text".to_string(),
+        ));
+    }
+    Ok(message.to_string())
+}
@@ -1,17 +1,28 @@
 mod ai;
 mod branding;
+mod chunking;
 mod commands;
+mod commit_lint;
+mod commit_message;
+mod commit_style;
+mod commit_trailers;
 mod config;
+mod diff_analysis;
 mod filters;
+mod llm_cache;
+mod llm_usage;
 mod llms;
+mod prompt_templates;
 mod prompts;
+mod redact;
 mod utils;
 mod web;
 
 use crate::commands::{
-    add, clone, commit, git_runner, hooks, ignore, init, pull, push, staging, stash, status,
+    add, cache, clone, commit, git_runner, hooks, ignore, init, models, pull, push, reword,
+    staging, stash, status, style, templates, usage,
 };
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(
@@ -22,6 +33,9 @@ use clap::{Parser, Subcommand};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Skip the AI response cache for this run
+    #[arg(long, global = true, default_value = "false")]
+    no_cache: bool,
 }
 
 #[derive(Subcommand)]
@@ -50,11 +64,44 @@ enum Commands {
         reword: bool,
         #[arg(long, default_value = "false")]
         ai: bool,
+        #[arg(long)]
+        candidates: Option<usize>,
+        #[arg(long)]
+        pick: Option<usize>,
+        /// Preview the diff exactly as it will be sent to the AI backend
+        #[arg(long, default_value = "false")]
+        show_redacted: bool,
+        /// Split the staged changes into several logical commits
+        #[arg(long, default_value = "false")]
+        split: bool,
+    },
+    /// Rewrite the messages of past commits, e.g. `git-ai reword HEAD~3`
+    Reword {
+        range: String,
+        /// Also rewrite commits already on a protected upstream branch
+        #[arg(long, default_value = "false")]
+        force: bool,
     },
     Web {},
-    InstallHook {},
+    InstallHook {
+        /// Also pre-fill messages for plain `git commit` via prepare-commit-msg
+        #[arg(long, default_value = "false")]
+        prepare_commit_msg: bool,
+        /// Also reject commits whose message breaks the [lint] rules via commit-msg
+        #[arg(long, default_value = "false")]
+        commit_msg: bool,
+    },
     UninstallHook {},
     Precommit {}, // ➡️ New command added here
+    /// Entry points called by the git hooks git-ai installs
+    Hook {
+        #[command(subcommand)]
+        command: HookSubcommand,
+    },
+    /// Check a commit message file against the [lint] rules (commit-msg hook)
+    LintMsg {
+        file: String,
+    },
     Push {},
     Status {},
     Pull {},
@@ -75,6 +122,30 @@ enum Commands {
     Clone {
         url: String,
     },
+    Cache {
+        #[command(subcommand)]
+        command: CacheSubcommand,
+    },
+    /// Show the commit conventions learned from this repository's history
+    Style {
+        #[command(subcommand)]
+        command: StyleSubcommand,
+    },
+    /// Manage models on the local Ollama server
+    Models {
+        #[command(subcommand)]
+        command: ModelsSubcommand,
+    },
+    /// Inspect and override the prompt templates used for AI calls
+    Prompts {
+        #[command(subcommand)]
+        command: PromptsSubcommand,
+    },
+    /// Token usage and estimated cost of AI calls
+    Usage {
+        #[arg(long, default_value = "false")]
+        all: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -85,13 +156,62 @@ enum StashSubcommand {
     Drop,
 }
 
+#[derive(Subcommand)]
+enum CacheSubcommand {
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum HookSubcommand {
+    PrepareCommitMsg {
+        file: String,
+        source: Option<String>,
+        sha: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum StyleSubcommand {
+    Show,
+}
+
+#[derive(Subcommand)]
+enum ModelsSubcommand {
+    /// Show installed models and whether the configured one is among them
+    List,
+    /// Download a model, the configured one by default
+    Pull { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum PromptsSubcommand {
+    /// Print the effective template, or list all of them
+    Show { name: Option<String> },
+    /// Copy the template into .git-ai-prompts (or ~/.git-ai/prompts) and edit it
+    Edit {
+        name: String,
+        #[arg(long, default_value = "false")]
+        user: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() {
-    ai::init_llm_backend(); // 💥 Initialize backend early
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-    branding::show_banner();
+    // Hook entry points print into `git commit` output, keep them quiet
+    if !matches!(
+        cli.command,
+        Commands::Hook { .. } | Commands::LintMsg { .. } | Commands::Precommit {}
+    ) {
+        branding::show_banner();
+    }
+    llm_usage::set_command(matches.subcommand_name().unwrap_or_default());
 
-    let cli = Cli::parse();
+    if cli.no_cache {
+        llm_cache::disable();
+    }
 
     match cli.command {
         Commands::Diff { prompt, profile } => {
@@ -106,8 +226,11 @@ async fn main() {
         Commands::Web {} => {
             web::start_server();
         }
-        Commands::InstallHook {} => {
-            hooks::install_hook();
+        Commands::InstallHook {
+            prepare_commit_msg,
+            commit_msg,
+        } => {
+            hooks::install_hook(prepare_commit_msg, commit_msg);
         }
         Commands::UninstallHook {} => {
             hooks::uninstall_hook();
@@ -115,14 +238,42 @@ async fn main() {
         Commands::Precommit {} => {
             hooks::run_precommit(); // ➡️ Run precommit checks
         }
+        Commands::Hook { command } => match command {
+            HookSubcommand::PrepareCommitMsg { file, source, .. } => {
+                hooks::run_prepare_commit_msg(file, source).await
+            }
+        },
+        Commands::LintMsg { file } => {
+            hooks::run_lint_msg(&file);
+        }
         Commands::Push {} => {
             push::push_changes();
         }
         Commands::Status {} => {
             status::show_git_status();
         }
-        Commands::Commit { amend, reword, ai } => {
-            commit::commit_changes(amend, reword, ai).await;
+        Commands::Commit {
+            amend,
+            reword,
+            ai,
+            candidates,
+            pick,
+            show_redacted,
+            split,
+        } => {
+            commit::commit_changes(commit::CommitOptions {
+                amend,
+                reword,
+                ai,
+                candidates,
+                pick,
+                show_redacted,
+                split,
+            })
+            .await;
+        }
+        Commands::Reword { range, force } => {
+            reword::reword_commits(&range, force).await;
         }
         Commands::Pull {} => {
             pull::smart_pull();
@@ -142,5 +293,22 @@ async fn main() {
         Commands::Clone { url } => {
             clone::smart_clone(url);
         }
+        Commands::Cache { command } => match command {
+            CacheSubcommand::Clear => cache::cache_handler("clear"),
+        },
+        Commands::Style { command } => match command {
+            StyleSubcommand::Show => style::style_handler("show"),
+        },
+        Commands::Models { command } => match command {
+            ModelsSubcommand::List => models::models_handler("list", None).await,
+            ModelsSubcommand::Pull { name } => models::models_handler("pull", name).await,
+        },
+        Commands::Prompts { command } => match command {
+            PromptsSubcommand::Show { name } => templates::show_template(name),
+            PromptsSubcommand::Edit { name, user } => templates::edit_template(&name, user),
+        },
+        Commands::Usage { all } => {
+            usage::show_usage(all);
+        }
     }
 }
@@ -0,0 +1,219 @@
+use crate::commit_message::REPAIR_PROMPT;
+use crate::llms::{COMMIT_FROM_SUMMARIES_PROMPT, COMMIT_SYSTEM_PROMPT, DIFF_SUMMARY_PROMPT};
+use crate::redact::redact;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([a-z_]+)\}").unwrap());
+
+// Per-repository overrides, e.g. .git-ai-prompts/commit.txt
+pub const REPO_TEMPLATE_DIR: &str = ".git-ai-prompts";
+
+const INIT_PROMPT: &str = "You are a Git repository initializer.\n\nGiven the following project files, Please:\n1. Guess the project type (e.g., Rust, Node.js, Python, etc.)\n2. Suggest a clean .gitignore\n3. Suggest a clean .git-ai-ignore\n4. Suggest a starter README.md\n\nReturn each section clearly titled.";
+
+const IGNORE_PROMPT: &str =
+    "You help keep Git history clean by suggesting .gitignore-style patterns. Reply with the patterns only, one per line.";
+
+const SPLIT_PROMPT: &str = "You split a staged Git diff into small, coherent commits. You are given numbered hunks. Group the hunks that belong to the same logical change, such as a refactor, a bug fix, a formatting sweep or a docs update. Every hunk goes into exactly one group, and groups are ordered so each commit builds on the ones before it. Respond with a single JSON object and nothing else, no markdown fences:\n{\"groups\": [{\"message\": \"conventional commit message for the group\", \"hunks\": [1, 2]}]}";
+
+const AMEND_PROMPT: &str = "You update a Git commit message after more changes were folded into the commit. You are given the current message and the combined diff of the amended commit. Keep what is still accurate, cover the new changes, and keep the style of the current message. Leave out trailers such as Signed-off-by, they are added back separately. Reply with the commit message only.";
+
+// (name, system prompt, user message, what it's used for)
+pub const BUILTIN_TEMPLATES: [(&str, &str, &str, &str); 8] = [
+    ("commit", COMMIT_SYSTEM_PROMPT, "{diff}", "commit message from the staged diff"),
+    (
+        "summarize-chunk",
+        DIFF_SUMMARY_PROMPT,
+        "{diff}",
+        "summary of one piece of an oversized diff",
+    ),
+    (
+        "commit-from-summaries",
+        COMMIT_FROM_SUMMARIES_PROMPT,
+        "{summaries}",
+        "commit message from chunk summaries",
+    ),
+    (
+        "repair",
+        REPAIR_PROMPT,
+        "Validation error: {error}\n\nPrevious output:\n{output}",
+        "fixes structured output that failed validation",
+    ),
+    (
+        "ignore",
+        IGNORE_PROMPT,
+        "Based on these project files:\n{files}\n\nSuggest a .gitignore-style list of patterns that should be ignored to keep Git history clean. Focus on junk files, build artifacts, temporary files, environment files, node_modules, target, etc.",
+        "`git-ai ignore --suggest`",
+    ),
+    (
+        "init",
+        INIT_PROMPT,
+        "\ngenerate for these project files: {files}",
+        "`git-ai init --magic` scaffolding",
+    ),
+    (
+        "amend",
+        AMEND_PROMPT,
+        "Current message:\n{message}\n\nCombined diff:\n{diff}",
+        "`git-ai commit --amend --ai` message update",
+    ),
+    (
+        "split",
+        SPLIT_PROMPT,
+        "{hunks}",
+        "`git-ai commit --split` grouping of staged hunks",
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub enum TemplateSource {
+    Repo(PathBuf),
+    User(PathBuf),
+    BuiltIn,
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub system: String,
+    pub user: String,
+    pub source: TemplateSource,
+}
+
+// ~/.git-ai/prompts, shared by every repository
+pub fn user_template_dir() -> Option<PathBuf> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()?;
+    Some(PathBuf::from(home).join(".git-ai").join("prompts"))
+}
+
+pub fn template_path(dir: PathBuf, name: &str) -> PathBuf {
+    dir.join(format!("{}.txt", name))
+}
+
+pub fn is_known(name: &str) -> bool {
+    BUILTIN_TEMPLATES
+        .iter()
+        .any(|(builtin, ..)| *builtin == name)
+}
+
+// Effective template: the repository file wins over the user file, which
+// wins over the built-in text
+pub fn load(name: &str) -> PromptTemplate {
+    let (_, system, user, _) = BUILTIN_TEMPLATES
+        .iter()
+        .find(|(builtin, ..)| *builtin == name)
+        .copied()
+        .unwrap_or((name, "", "{diff}", ""));
+    let builtin = PromptTemplate {
+        name: name.to_string(),
+        system: system.to_string(),
+        user: user.to_string(),
+        source: TemplateSource::BuiltIn,
+    };
+
+    let candidates = [
+        Some(template_path(PathBuf::from(REPO_TEMPLATE_DIR), name)),
+        user_template_dir().map(|dir| template_path(dir, name)),
+    ];
+    for (index, path) in candidates.into_iter().flatten().enumerate() {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let (system, user) = parse(&content, &builtin.user);
+        return PromptTemplate {
+            system,
+            user,
+            source: if index == 0 {
+                TemplateSource::Repo(path)
+            } else {
+                TemplateSource::User(path)
+            },
+            ..builtin
+        };
+    }
+
+    builtin
+}
+
+// A template file is the system prompt, optionally split into `[system]`
+// and `[user]` sections when the user message should change too
+fn parse(content: &str, default_user: &str) -> (String, String) {
+    let content = content.trim();
+    let Some(rest) = content.strip_prefix("[system]") else {
+        return (content.to_string(), default_user.to_string());
+    };
+
+    match rest.split_once("\n[user]") {
+        Some((system, user)) => (system.trim().to_string(), user.trim().to_string()),
+        None => (rest.trim().to_string(), default_user.to_string()),
+    }
+}
+
+impl PromptTemplate {
+    // File contents for `git-ai prompts edit`, the inverse of `parse`
+    pub fn to_file(&self) -> String {
+        format!("[system]\n{}\n\n[user]\n{}\n", self.system, self.user)
+    }
+
+    // (system prompt, user message) with placeholders filled in
+    pub fn render(&self, vars: &[(&str, &str)]) -> (String, String) {
+        let mut values: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let text = format!("{}{}", self.system, self.user);
+        for (key, compute) in [
+            ("branch", current_branch as fn() -> String),
+            ("history", recent_history),
+            ("files", staged_files),
+        ] {
+            if !values.contains_key(key) && text.contains(&format!("{{{}}}", key)) {
+                values.insert(key.to_string(), compute());
+            }
+        }
+
+        (
+            substitute(&self.system, &values),
+            substitute(&self.user, &values),
+        )
+    }
+}
+
+// Single pass, so a diff that happens to contain "{branch}" stays untouched
+fn substitute(text: &str, values: &HashMap<String, String>) -> String {
+    PLACEHOLDER
+        .replace_all(text, |caps: &Captures| {
+            values
+                .get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn git_output(args: &[&str]) -> String {
+    Command::new("git")
+        .args(args)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+fn current_branch() -> String {
+    git_output(&["rev-parse", "--abbrev-ref", "HEAD"])
+}
+
+// Past messages and file names can hold secrets too, like the diff
+fn recent_history() -> String {
+    redact(&git_output(&["log", "--oneline", "-n", "10"])).text
+}
+
+fn staged_files() -> String {
+    redact(&git_output(&["diff", "--cached", "--name-only"])).text
+}
@@ -112,7 +112,7 @@ pub fn show_in_pager(content: &str) {
             break;
         }
 
-        print!("{}", "-- More -- (press enter to continue, q to quit) ");
+        print!("-- More -- (press enter to continue, q to quit) ");
         io::stdout().flush().unwrap();
         let mut input = String::new();
         io::stdin().read_line(&mut input).unwrap();
@@ -156,3 +156,33 @@ pub fn has_staged_changes() -> bool {
 
     !status.success()
 }
+
+// Open a file in `editor` from .git-ai, then git's core.editor, then
+// $VISUAL/$EDITOR, then vi, and wait for it to close. Values like
+// "code --wait" keep their arguments.
+pub fn open_in_editor(path: &Path) -> bool {
+    let core_editor = || {
+        Command::new("git")
+            .args(["config", "core.editor"])
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let non_empty = |editor: Option<String>| editor.filter(|editor| !editor.trim().is_empty());
+
+    let editor = non_empty(crate::config::GIT_AI_CONFIG.editor.clone())
+        .or_else(|| non_empty(core_editor()))
+        .or_else(|| non_empty(std::env::var("VISUAL").ok()))
+        .or_else(|| non_empty(std::env::var("EDITOR").ok()))
+        .unwrap_or_else(|| "vi".to_string());
+
+    // Run it through the shell like git does, so quoted paths with spaces work
+    Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg("--")
+        .arg(path)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
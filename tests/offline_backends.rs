@@ -0,0 +1,348 @@
+// End-to-end runs of the binary against throwaway repositories, using the
+// offline backends (mock, replay) so no network or API key is needed
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+struct TempRepo {
+    path: PathBuf,
+}
+
+impl TempRepo {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("git-ai-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        let repo = TempRepo { path };
+
+        repo.git(&["init", "-q"]);
+        repo.git(&["config", "user.name", "Test"]);
+        repo.git(&["config", "user.email", "test@example.com"]);
+        repo
+    }
+
+    fn git(&self, args: &[&str]) -> String {
+        self.git_with(args, &[])
+    }
+
+    fn git_with(&self, args: &[&str], envs: &[(&str, &str)]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.path)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("HOME", &self.path)
+            .envs(envs.iter().copied())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn stage(&self, file: &str, content: &str) {
+        fs::write(self.path.join(file), content).unwrap();
+        self.git(&["add", file]);
+    }
+
+    // HOME points into the repository so the usage ledger, user prompt
+    // templates and global git config of the machine stay out of the way
+    fn git_ai_command(&self, args: &[&str], envs: &[(&str, &str)]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_git-ai"));
+        command
+            .args(args)
+            .current_dir(&self.path)
+            .env("HOME", &self.path)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env_remove("GIT_AI_RECORD")
+            .env_remove("GIT_AI_MOCK_RESPONSE")
+            .env_remove("GIT_AI_PAIR");
+        for (key, value) in envs {
+            command.env(key, value);
+        }
+        command
+    }
+
+    fn git_ai(&self, args: &[&str], envs: &[(&str, &str)]) -> Output {
+        self.git_ai_command(args, envs)
+            .arg("--no-cache")
+            .stdin(Stdio::null())
+            .output()
+            .unwrap()
+    }
+
+    // Same, with `answers` typed into the prompts
+    fn git_ai_answering(&self, args: &[&str], envs: &[(&str, &str)], answers: &str) -> Output {
+        let mut child = self
+            .git_ai_command(args, envs)
+            .arg("--no-cache")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(answers.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    // Commit whatever is staged, dated `hours` ago
+    fn commit_hours_ago(&self, message: &str, hours: u64) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let date = format!("@{} +0000", now - hours * 3600);
+        self.git_with(
+            &["commit", "-q", "--allow-empty", "-m", message],
+            &[("GIT_AUTHOR_DATE", &date), ("GIT_COMMITTER_DATE", &date)],
+        );
+    }
+
+    fn subject(&self) -> String {
+        self.git(&["log", "-1", "--format=%s"])
+    }
+
+    fn file(&self, name: &str) -> String {
+        fs::read_to_string(self.path.join(name)).unwrap_or_default()
+    }
+}
+
+impl Drop for TempRepo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn mock_commit_with_pick_uses_the_default_template() {
+    let repo = TempRepo::new("mock-commit");
+    repo.stage("notes.txt", "hello\n");
+
+    let output = repo.git_ai(&["commit", "--pick", "1"], &[("GIT_AI_LLM", "mock")]);
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(repo.subject(), "chore: update notes.txt");
+}
+
+#[test]
+fn mock_commit_with_plain_response_in_structured_mode() {
+    let repo = TempRepo::new("mock-structured");
+    repo.stage("lib.rs", "pub fn answer() -> u32 { 42 }\n");
+
+    let output = repo.git_ai(
+        &["commit", "--pick", "1"],
+        &[
+            ("GIT_AI_LLM", "mock"),
+            ("GIT_AI_MOCK_RESPONSE", "feat(lib): add answer"),
+        ],
+    );
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(repo.subject(), "feat(lib): add answer");
+}
+
+#[test]
+fn pick_fails_without_a_backend() {
+    let repo = TempRepo::new("pick-nollm");
+    repo.stage("notes.txt", "hello\n");
+
+    let output = repo.git_ai(&["commit", "--pick", "1"], &[("GIT_AI_LLM", "nollm")]);
+
+    assert!(!output.status.success());
+    assert!(repo.git(&["rev-list", "--all"]).is_empty());
+}
+
+#[test]
+fn replay_returns_the_recorded_commit_message() {
+    let repo = TempRepo::new("replay-commit");
+    repo.stage("notes.txt", "hello\n");
+
+    let recorded = repo.git_ai(
+        &["commit", "--pick", "1"],
+        &[
+            ("GIT_AI_LLM", "replay"),
+            ("GIT_AI_RECORD", "mock"),
+            ("GIT_AI_MOCK_RESPONSE", "docs: add notes"),
+        ],
+    );
+    assert!(recorded.status.success(), "{}", stdout(&recorded));
+    assert!(repo.path.join(".git-ai-cassette.json").exists());
+
+    // Same staged change again, answered from the cassette only
+    repo.git(&["update-ref", "-d", "HEAD"]);
+    let replayed = repo.git_ai(&["commit", "--pick", "1"], &[("GIT_AI_LLM", "replay")]);
+
+    assert!(replayed.status.success(), "{}", stdout(&replayed));
+    assert_eq!(repo.subject(), "docs: add notes");
+}
+
+#[test]
+fn mock_ignore_suggestions_are_saved() {
+    let repo = TempRepo::new("mock-ignore");
+    fs::write(repo.path.join("debug.log"), "noise\n").unwrap();
+
+    let output = repo.git_ai(
+        &["ignore", "--suggest", "--save"],
+        &[("GIT_AI_LLM", "mock"), ("GIT_AI_MOCK_RESPONSE", "*.log")],
+    );
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(repo.file(".gitignore").contains("*.log"));
+    assert!(repo.file(".git-ai-ignore").contains("*.log"));
+}
+
+#[test]
+fn split_with_a_renamed_file_across_two_commits() {
+    let repo = TempRepo::new("split-rename");
+    let lines: Vec<String> = (1..=30).map(|n| format!("line {}\n", n)).collect();
+    repo.stage("old.txt", &lines.concat());
+    repo.git(&["commit", "-q", "-m", "init"]);
+
+    // Two hunks far apart in the renamed file
+    repo.git(&["mv", "old.txt", "new.txt"]);
+    let mut changed = lines.clone();
+    changed[1] = "line 2 changed\n".to_string();
+    changed[27] = "line 28 changed\n".to_string();
+    repo.stage("new.txt", &changed.concat());
+
+    let output = repo.git_ai_answering(
+        &["commit", "--split"],
+        &[
+            ("GIT_AI_LLM", "mock"),
+            (
+                "GIT_AI_MOCK_RESPONSE",
+                r#"{"groups": [{"message": "refactor: rename old.txt", "hunks": [1]}, {"message": "fix: change line 28", "hunks": [2]}]}"#,
+            ),
+        ],
+        "y\n",
+    );
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(
+        repo.git(&["log", "--format=%s", "-2"]),
+        "fix: change line 28\nrefactor: rename old.txt"
+    );
+    assert_eq!(repo.git(&["show", "HEAD:new.txt"]), changed.concat().trim());
+    assert!(repo.git(&["ls-files", "old.txt"]).is_empty());
+}
+
+const ALICE: &str = "Co-authored-by: Alice Doe <alice@example.com>";
+
+#[test]
+fn pair_history_is_off_by_default() {
+    let repo = TempRepo::new("pair-off");
+    repo.commit_hours_ago(&format!("feat: pair\n\n{}", ALICE), 1);
+    repo.stage("notes.txt", "hello\n");
+
+    let output = repo.git_ai(&["commit", "--pick", "1"], &[("GIT_AI_LLM", "mock")]);
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(!repo.git(&["log", "-1", "--format=%B"]).contains(ALICE));
+}
+
+#[test]
+fn pair_history_expires_from_the_original_pairing() {
+    let repo = TempRepo::new("pair-expiry");
+    repo.stage(".git-ai", "[trailers]\npair_history_hours = 8\n");
+    repo.commit_hours_ago("chore: solo", 12);
+
+    // Paired 5 hours ago: carried over
+    repo.commit_hours_ago(&format!("feat: pair\n\n{}", ALICE), 5);
+    repo.stage("a.txt", "a\n");
+    let output = repo.git_ai(&["commit", "--pick", "1"], &[("GIT_AI_LLM", "mock")]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(repo.git(&["log", "-1", "--format=%B"]).contains(ALICE));
+
+    // Paired 10 hours ago and carried over since: expired
+    repo.git(&["reset", "-q", "--hard", "HEAD~2"]);
+    repo.commit_hours_ago(&format!("feat: pair\n\n{}", ALICE), 10);
+    repo.commit_hours_ago(&format!("chore: carried\n\n{}", ALICE), 3);
+    repo.stage("b.txt", "b\n");
+    let output = repo.git_ai(&["commit", "--pick", "1"], &[("GIT_AI_LLM", "mock")]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(!repo.git(&["log", "-1", "--format=%B"]).contains(ALICE));
+}
+
+#[test]
+fn fallback_answers_are_not_cached() {
+    let repo = TempRepo::new("fallback-cache");
+    fs::write(
+        repo.path.join(".git-ai"),
+        "llm_backend = \"ollama\"\nfallback = [\"heuristic\"]\nmax_retries = 0\n\n[providers.ollama]\nbase_url = \"http://127.0.0.1:9\"\n",
+    )
+    .unwrap();
+    repo.stage("notes.txt", "hello\n");
+
+    let output = repo
+        .git_ai_command(&["commit", "--pick", "1"], &[])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    let cache = repo.path.join(".git/git-ai/cache");
+    assert!(fs::read_dir(cache).map_or(true, |mut entries| entries.next().is_none()));
+}
+
+#[test]
+fn reword_keeps_co_authors_off_past_commits() {
+    let repo = TempRepo::new("reword-trailers");
+    repo.git(&["checkout", "-q", "-b", "work"]);
+    repo.stage("a.txt", "a\n");
+    repo.git(&["commit", "-q", "-m", "a"]);
+    repo.stage("b.txt", "b\n");
+    repo.git(&["commit", "-q", "-m", "b"]);
+
+    let output = repo.git_ai_answering(
+        &["reword", "HEAD~1"],
+        &[
+            ("GIT_AI_LLM", "mock"),
+            ("GIT_AI_PAIR", "Bob <bob@example.com>"),
+        ],
+        "a\ny\n",
+    );
+
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(repo.subject(), "chore: update b.txt");
+    assert!(!repo
+        .git(&["log", "-1", "--format=%B"])
+        .contains("Co-authored-by"));
+}
+
+#[test]
+fn install_hook_leaves_commit_msg_opt_in() {
+    let repo = TempRepo::new("hooks-opt-in");
+
+    let output = repo.git_ai(&["install-hook"], &[]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(repo.path.join(".git/hooks/pre-commit").exists());
+    assert!(!repo.path.join(".git/hooks/commit-msg").exists());
+
+    let output = repo.git_ai(&["install-hook", "--commit-msg"], &[]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(repo.path.join(".git/hooks/commit-msg").exists());
+}
+
+#[test]
+fn lint_msg_drops_comments_in_core_comment_char() {
+    let repo = TempRepo::new("lint-comment-char");
+    repo.git(&["config", "core.commentChar", ";"]);
+    let comment = "; Please enter the commit message for your changes. Lines starting with ';' will be ignored.";
+    fs::write(
+        repo.path.join("MSG"),
+        format!("feat: add notes\n\n{}\n;\n; On branch main\n", comment),
+    )
+    .unwrap();
+
+    let output = repo.git_ai(&["lint-msg", "MSG"], &[]);
+
+    assert!(output.status.success(), "{}", stdout(&output));
+}